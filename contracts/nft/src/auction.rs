@@ -0,0 +1,184 @@
+use crate::*;
+
+/// english auction of a miner, the miner is held by this contract until settled,
+/// so are the NEAR or vBTC of the highest bid.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Auction {
+    pub seller_id: AccountId,
+    pub currency: Currency,
+    /// lowest acceptable bid
+    pub reserve: U128,
+    /// no more bids from this block on
+    pub end_block: U64,
+    pub highest_bidder: Option<AccountId>,
+    pub highest_bid: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+
+    /// escrow the miner in this contract and start an auction of it.
+    /// Caller must attach deposit to cover storage of the auction, the rest is refunded.
+    #[payable]
+    pub fn start_auction(&mut self, token_id: TokenId, reserve: U128, currency: Currency, end_block: U64) {
+        let initial_storage_usage = env::storage_usage();
+        let seller_id = env::predecessor_account_id();
+        assert!(end_block.0 > env::block_index(), "Auction should end in the future");
+        let miner = self.data().miners_by_id.get(&token_id).expect("Miner doesn't exist");
+        assert_eq!(miner.switch, PW_OFF, "Miner should be powered off before auctioned");
+        if currency == Currency::Vbtc {
+            self.assert_vbtc_token_set();
+        }
+        let (previous_owner_id, approved_account_ids) = self.internal_transfer(
+            &seller_id, &env::current_account_id(), &token_id, None, None,
+        );
+        assert_eq!(previous_owner_id, seller_id, "Only owner can auction this miner");
+        refund_approved_account_ids(previous_owner_id, &approved_account_ids);
+
+        self.data_mut().auctions.insert(&token_id, &Auction {
+            seller_id: seller_id.clone(),
+            currency,
+            reserve,
+            end_block,
+            highest_bidder: None,
+            highest_bid: 0.into(),
+        });
+        env::log(
+            format!(
+                "Auction of {} by @{} started, reserve {} {:?}, ends at block {}.",
+                token_id, seller_id, reserve.0, currency, end_block.0
+            ).as_bytes());
+        deposit_refund(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    /// bid in NEAR, the whole attached deposit is the bid
+    #[payable]
+    pub fn bid_miner(&mut self, token_id: TokenId) {
+        let auction = self.data().auctions.get(&token_id).expect("Auction doesn't exist");
+        assert_eq!(auction.currency, Currency::Near, "This auction is not in NEAR");
+        self.internal_bid(&token_id, &env::predecessor_account_id(), env::attached_deposit());
+    }
+
+    /// seller can take back the miner if nobody bid yet
+    pub fn cancel_auction(&mut self, token_id: TokenId) {
+        let auction = self.data().auctions.get(&token_id).expect("Auction doesn't exist");
+        assert_eq!(auction.seller_id, env::predecessor_account_id(), "Only seller can cancel this auction");
+        assert!(auction.highest_bidder.is_none(), "Auction already has bids");
+        self.internal_close_auction(&token_id, &auction.seller_id);
+        env::log(format!("Auction of {} canceled.", token_id).as_bytes());
+    }
+
+    /// anyone can settle an ended auction, the miner goes to the highest bidder
+    /// and the bid to the seller, or the miner goes back to the seller if there is no bid.
+    pub fn settle_auction(&mut self, token_id: TokenId) {
+        let auction = self.data().auctions.get(&token_id).expect("Auction doesn't exist");
+        assert!(env::block_index() >= auction.end_block.0, "Auction not ended yet");
+        match auction.highest_bidder {
+            Some(winner_id) => {
+                self.internal_close_auction(&token_id, &winner_id);
+                self.internal_pay(&auction.seller_id, auction.currency, auction.highest_bid.into());
+                env::log(
+                    format!(
+                        "Auction of {} won by @{} at {} {:?}.",
+                        token_id, winner_id, auction.highest_bid.0, auction.currency
+                    ).as_bytes());
+            }
+            None => {
+                self.internal_close_auction(&token_id, &auction.seller_id);
+                env::log(format!("Auction of {} ended without bids.", token_id).as_bytes());
+            }
+        }
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    pub fn get_auction(&self, token_id: TokenId) -> Option<Auction> {
+        self.data().auctions.get(&token_id)
+    }
+
+    pub fn list_auctions(&self, from_index: u64, limit: u64) -> HashMap<TokenId, Auction> {
+        let keys = self.data().auctions.keys_as_vector();
+
+        (from_index..std::cmp::min(from_index + limit, keys.len())).map(
+            |index| (
+                keys.get(index).unwrap(),
+                self.data().auctions.get(&keys.get(index).unwrap()).unwrap())
+        ).collect::<HashMap<_,_>>()
+    }
+}
+
+impl Contract {
+
+    /// record a new highest bid, already held by this contract, and refund the previous one
+    pub(crate) fn internal_bid(&mut self, token_id: &TokenId, bidder_id: &AccountId, amount: Balance) {
+        let mut auction = self.data().auctions.get(token_id).expect("Auction doesn't exist");
+        assert!(env::block_index() < auction.end_block.0, "Auction already ended");
+        assert!(amount >= auction.reserve.0, "Bid should be at least {}", auction.reserve.0);
+        assert!(amount > auction.highest_bid.0, "Bid should be higher than {}", auction.highest_bid.0);
+
+        if let Some(previous_bidder_id) = auction.highest_bidder.replace(bidder_id.clone()) {
+            self.internal_pay(&previous_bidder_id, auction.currency, auction.highest_bid.into());
+        }
+        auction.highest_bid = amount.into();
+        self.data_mut().auctions.insert(token_id, &auction);
+        env::log(
+            format!(
+                "@{} bid {} {:?} for {}.", bidder_id, amount, auction.currency, token_id
+            ).as_bytes());
+    }
+
+    /// release the miner to receiver, storage freed by the auction and escrow is returned to seller
+    fn internal_close_auction(&mut self, token_id: &TokenId, receiver_id: &AccountId) {
+        let initial_storage_usage = env::storage_usage();
+        let auction = self.data_mut().auctions.remove(token_id).expect("Auction doesn't exist");
+        self.internal_transfer(&env::current_account_id(), receiver_id, token_id, None, None);
+        refund_released_storage(
+            auction.seller_id, initial_storage_usage.saturating_sub(env::storage_usage()),
+        );
+    }
+
+    /// pay NEAR or vBTC held by this contract
+    fn internal_pay(&self, receiver_id: &AccountId, currency: Currency, amount: Balance) {
+        match currency {
+            Currency::Near => { Promise::new(receiver_id.clone()).transfer(amount); }
+            Currency::Vbtc => { self.internal_send_vbtc(receiver_id, amount); }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::{testing_env, MockedBlockchain};
+
+    #[test]
+    fn test_auction_with_near() {
+        let mut contract = setup_contract(accounts(2), 1);
+        let token_id = "mid-001#0".to_string();
+
+        call_with(accounts(2), ONE_NEAR);
+        contract.start_auction(token_id.clone(), ONE_NEAR.into(), Currency::Near, 100.into());
+        assert_eq!(miners_of(&contract, accounts(0)), vec![token_id.clone()]);
+        assert!(miners_of(&contract, accounts(2)).is_empty());
+
+        call_with(accounts(3), ONE_NEAR);
+        contract.bid_miner(token_id.clone());
+        call_with(accounts(4), 2 * ONE_NEAR);
+        contract.bid_miner(token_id.clone());
+        let auction = contract.get_auction(token_id.clone()).unwrap();
+        assert_eq!(auction.highest_bidder, Some(accounts(4).into()));
+        assert_eq!(auction.highest_bid.0, 2 * ONE_NEAR);
+
+        testing_env!(get_context(accounts(3)).block_index(100).build());
+        contract.settle_auction(token_id.clone());
+        assert!(contract.get_auction(token_id.clone()).is_none());
+        assert_eq!(contract.nft_token(token_id.clone()).unwrap().owner_id, accounts(4).to_string());
+        assert_eq!(miners_of(&contract, accounts(4)), vec![token_id]);
+        assert!(miners_of(&contract, accounts(0)).is_empty());
+    }
+}
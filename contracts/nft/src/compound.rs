@@ -0,0 +1,139 @@
+use crate::*;
+
+/// opt-in setting to turn part of mining award into power cards,
+/// bought at vBTC price of power_card_prices and applied to owner's miners in settlement.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AutoCompound {
+    /// percent of each award used to buy cards, 1 to 100
+    pub percentage: u8,
+    pub power_card: TokenMetadataId,
+    /// part of each award always paid out in vBTC
+    pub min_reserve: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+
+    /// opt in or update auto-compounding of caller's award.
+    /// Caller must attach deposit to cover storage of the setting, the rest is refunded.
+    #[payable]
+    pub fn set_auto_compound(&mut self, percentage: u8, power_card: TokenMetadataId, min_reserve: U128) {
+        let initial_storage_usage = env::storage_usage();
+        assert!(percentage > 0 && percentage <= 100, "Percentage should be in 1 to 100");
+        assert!(
+            self.data().power_card_prices.get(&power_card).is_some(),
+            "Power card is not for sale"
+        );
+        let account_id = env::predecessor_account_id();
+        self.data_mut().auto_compounds.insert(&account_id, &AutoCompound {
+            percentage,
+            power_card: power_card.clone(),
+            min_reserve,
+        });
+        env::log(
+            format!(
+                "@{} compounds {}% of award into {}, reserving {} vBTC.",
+                account_id, percentage, power_card, min_reserve.0
+            ).as_bytes());
+        deposit_refund(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    /// opt out, storage deposit of the setting is returned
+    pub fn remove_auto_compound(&mut self) {
+        let initial_storage_usage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        if self.data_mut().auto_compounds.remove(&account_id).is_some() {
+            env::log(format!("@{} stops auto-compounding.", account_id).as_bytes());
+            refund_released_storage(account_id, initial_storage_usage.saturating_sub(env::storage_usage()));
+        }
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    pub fn get_auto_compound(&self, account_id: ValidAccountId) -> Option<AutoCompound> {
+        self.data().auto_compounds.get(account_id.as_ref())
+    }
+}
+
+impl Contract {
+
+    /// called in settlement before award is sent,
+    /// buys cards with part of the award, applies them one per running miner of the winner
+    /// not leased out, cards left over stay in its powers_per_owner. Returns award left to send.
+    pub(crate) fn settle_auto_compound(&mut self, account_id: &AccountId, award: Balance) -> Balance {
+        let setting = match self.data().auto_compounds.get(account_id) {
+            Some(setting) => setting,
+            None => return award,
+        };
+        // card may be taken off sale after the setting
+        let price = match self.data().power_card_prices.get(&setting.power_card) {
+            Some(price) if price > 0 => price,
+            _ => return award,
+        };
+        let budget = std::cmp::min(
+            award * Balance::from(setting.percentage) / 100,
+            award.saturating_sub(setting.min_reserve.into()),
+        );
+        let count = (budget / price) as u32;
+        if count == 0 {
+            return award;
+        }
+        let cost = price * Balance::from(count);
+        self.internal_add_powers(account_id, &setting.power_card, count);
+
+        let power = self.get_card_power(&setting.power_card);
+        let token_ids: Vec<TokenId> = self.data().miners_per_owner.get(account_id)
+            .map(|miners| miners.iter().flat_map(|(_, tokens)| tokens.to_vec()).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|token_id| {
+                let miner = self.data().miners_by_id.get(token_id).expect("Internal Error: Miner not exist.");
+                miner.status == ST_NORMAL && miner.switch == PW_ON && self.data().leases.get(token_id).is_none()
+            })
+            .collect();
+        let applied = std::cmp::min(count as usize, token_ids.len());
+        if applied > 0 {
+            self.internal_remove_powers(account_id, &setting.power_card, applied as u32);
+            for token_id in token_ids.iter().take(applied) {
+                self.internal_apply_power(token_id, power);
+            }
+        }
+        env::log(
+            format!(
+                "Compound {} vBTC of @{} into {} power card {}, {} applied to miners.",
+                cost, account_id, count, setting.power_card, applied
+            ).as_bytes());
+        award - cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn test_compound_into_running_miners() {
+        let mut contract = setup_contract(accounts(2), 2);
+        let card = "power-001".to_string();
+        contract.issue_power_cards(accounts(2), card.clone(), power_metadata(5, 100));
+        contract.set_power_card_price(card.clone(), Some(10.into()));
+
+        call_with(accounts(2), ONE_NEAR);
+        contract.consume_powers_by_tokens(card.clone(), vec!["mid-001#0".to_string()]);
+        contract.batch_poweron_miners(vec!["mid-001#0".to_string()]);
+        contract.set_auto_compound(100, card.clone(), 0.into());
+
+        assert_eq!(contract.settle_auto_compound(accounts(2).as_ref(), 35), 5);
+        // only the running miner gets a card, at once extending its deadline
+        let running = contract.nft_token("mid-001#0".to_string()).unwrap();
+        assert_eq!(running.power_deadline, 20);
+        assert_eq!(contract.nft_token("mid-001#1".to_string()).unwrap().power_left, 0);
+        let powers = contract.data().powers_per_owner.get(accounts(2).as_ref()).unwrap();
+        assert_eq!(powers.get(&card), Some(6));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::*;
+
+/// a param change takes effect at least 24 epochs after it was queued
+pub const DEFAULT_PARAM_TIMELOCK: MiningEpoch = 24;
+
+/// mining parameters that admin can change after init,
+/// each change goes to pending queue and takes effect after param_timelock epochs
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum MiningParam {
+    EpochAward(U128),
+    HalvingInterval(MiningEpoch),
+    EmissionCap(Option<U128>),
+    MinIntervalOfEpoch(U64),
+    ParamTimelock(MiningEpoch),
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ParamChange {
+    pub param: MiningParam,
+    /// the change would be applied in the settlement that enters this epoch
+    pub effective_epoch: MiningEpoch,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableMiningParams {
+    pub epoch_award: U128,
+    pub halving_interval: MiningEpoch,
+    pub emission_cap: Option<U128>,
+    pub min_interval_of_epoch: U64,
+    pub param_timelock: MiningEpoch,
+    pub pending_changes: HashMap<u64, ParamChange>,
+}
+
+#[near_bindgen]
+impl Contract {
+
+    /// queue a param change, returns id of the change
+    pub fn queue_param_change(&mut self, param: MiningParam) -> U64 {
+        self.assert_role(Role::Admin);
+
+        let id = self.data().next_param_change_id;
+        self.data_mut().next_param_change_id += 1;
+        let effective_epoch = self.data().current_mining_epoch + self.data().param_timelock;
+        self.data_mut().pending_param_changes.insert(&id, &ParamChange { param, effective_epoch });
+
+        env::log(
+            format!(
+                "Param change {} queued, effective in epoch {}.", id, effective_epoch
+            ).as_bytes());
+        id.into()
+    }
+
+    pub fn cancel_param_change(&mut self, id: U64) {
+        self.assert_role(Role::Admin);
+
+        let id: u64 = id.into();
+        self.data_mut().pending_param_changes.remove(&id).expect("Param change doesn't exist");
+        env::log(format!("Param change {} canceled.", id).as_bytes());
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    /// current mining params and all pending changes
+    pub fn get_mining_params(&self) -> HumanReadableMiningParams {
+        HumanReadableMiningParams {
+            epoch_award: self.data().epoch_award.into(),
+            halving_interval: self.data().halving_interval,
+            emission_cap: self.data().emission_cap.map(|v| v.into()),
+            min_interval_of_epoch: self.data().min_interval_of_epoch.into(),
+            param_timelock: self.data().param_timelock,
+            pending_changes: self.data().pending_param_changes.iter().collect(),
+        }
+    }
+}
+
+impl Contract {
+
+    /// called in mining settlement after entering new epoch,
+    /// to apply param changes that reach their effective epoch
+    pub(crate) fn settle_param_changes(&mut self) {
+        let current_mining_epoch = self.data().current_mining_epoch;
+        let mut due_ids: Vec<u64> = self.data().pending_param_changes.iter()
+            .filter(|(_, change)| change.effective_epoch <= current_mining_epoch)
+            .map(|(id, _)| id)
+            .collect();
+        // apply in the order they were queued
+        due_ids.sort_unstable();
+        for id in due_ids {
+            let change = self.data_mut().pending_param_changes.remove(&id).unwrap();
+            match change.param {
+                MiningParam::EpochAward(value) => {
+                    self.data_mut().epoch_award = value.into();
+                    self.data_mut().award_base_epoch = current_mining_epoch;
+                }
+                MiningParam::HalvingInterval(value) => {
+                    // keep the award reached so far, halve from now on by the new interval
+                    let award = self.get_scheduled_award(current_mining_epoch);
+                    self.data_mut().epoch_award = award;
+                    self.data_mut().award_base_epoch = current_mining_epoch;
+                    self.data_mut().halving_interval = value;
+                }
+                MiningParam::EmissionCap(value) => self.data_mut().emission_cap = value.map(|v| v.into()),
+                MiningParam::MinIntervalOfEpoch(value) => self.data_mut().min_interval_of_epoch = value.into(),
+                MiningParam::ParamTimelock(value) => self.data_mut().param_timelock = value,
+            }
+            env::log(
+                format!(
+                    "Param change {} applied in epoch {}.", id, current_mining_epoch
+                ).as_bytes());
+        }
+    }
+}
@@ -13,47 +13,27 @@ impl Contract {
     }
 
     pub fn batch_transfer_powers(&mut self, receiver_id: ValidAccountId, power_card: TokenMetadataId, amount: u32) {
+        self.assert_not_paused(PAUSE_TRANSFER);
         let sender_id = env::predecessor_account_id();
-        // check sender has enough cards to transfer
-        let mut power_map = self.powers_per_owner.get(&sender_id)
-            .expect("Sender has insurficient card to transfer.");
-
-        let num_sender_has = power_map.get(&power_card)
-            .expect("Sender has insurficient card to transfer.");
-        
-        if num_sender_has < amount {
-            env::panic("Sender has insurficient card to transfer.".as_bytes());
-        }
-        
-        // change sender's count
-        let remain = num_sender_has - amount;
-        if remain > 0 {
-            power_map.insert(&power_card, &remain);
-            self.powers_per_owner.insert(&sender_id, &power_map);
-        } else {
-            power_map.remove(&power_card);
-            if power_map.keys_as_vector().len() > 0 {
-                self.powers_per_owner.insert(&sender_id, &power_map);
-            } else {
-                self.powers_per_owner.remove(&sender_id);
-            }
-        }
-        // change receiver's count
-        let mut recv_power_map = self.powers_per_owner.get(receiver_id.as_ref())
-            .unwrap_or(UnorderedMap::new(unique_prefix(receiver_id.as_ref())));
-        let num_recv_has = recv_power_map.get(&power_card).unwrap_or(0);
-        recv_power_map.insert(&power_card, &(num_recv_has + amount));
-        self.powers_per_owner.insert(receiver_id.as_ref(), &recv_power_map);
-        
+        self.internal_remove_powers(&sender_id, &power_card, amount);
+        self.internal_add_powers(receiver_id.as_ref(), &power_card, amount);
     }
 
+    /// apply one power card of the caller to each of the miners
     pub fn consume_powers_by_tokens(&mut self, power_card: TokenMetadataId, token_ids: Vec<TokenId>
     ) {
+        let operator = env::predecessor_account_id();
+        let power = self.get_card_power(&power_card);
+        self.internal_remove_powers(&operator, &power_card, token_ids.len() as u32);
+        for token_id in token_ids.iter() {
+            let miner = self.data().miners_by_id.get(token_id).expect("Miner doesn't exist");
+            self.assert_miner_control(token_id, &miner, &operator);
+            self.internal_apply_power(token_id, power);
+        }
         env::log(
             format!(
-                "This function is underconstruction. {}, {}", token_ids.len(), power_card
+                "@{} applied power card {} to {} miners.", operator, power_card, token_ids.len()
             ).as_bytes());
-
     }
 
     pub fn consume_powers_by_tokenmetadata(&mut self, 
@@ -74,12 +54,12 @@ impl Contract {
     /// list all miner types created in this contract
     pub fn list_miner_types(&self, from_index: u64, limit: u64
     ) ->HashMap<TokenMetadataId, TokenMetadata> {
-        let keys = self.token_metadata_by_id.keys_as_vector();
+        let keys = self.data().token_metadata_by_id.keys_as_vector();
 
         (from_index..std::cmp::min(from_index + limit, keys.len())).map(
             |index| (
                 keys.get(index).unwrap(), 
-                self.token_metadata_by_id.get(&keys.get(index).unwrap()).unwrap())
+                self.data().token_metadata_by_id.get(&keys.get(index).unwrap()).unwrap())
         ).collect::<HashMap<_,_>>()
     }
 
@@ -88,7 +68,7 @@ impl Contract {
         from_index: u64, limit: u64
     ) ->HashMap<TokenMetadataId, TokenMetadata> {
 
-        let own_tokens = self.miners_per_owner.get(
+        let own_tokens = self.data().miners_per_owner.get(
             owner_id.as_ref()).unwrap_or(UnorderedMap::new(unique_prefix(owner_id.as_ref())));
             
         let keys = own_tokens.keys_as_vector();
@@ -96,7 +76,7 @@ impl Contract {
         (from_index..std::cmp::min(from_index + limit, keys.len())).map(
             |index| (
                 keys.get(index).unwrap(), 
-                self.token_metadata_by_id.get(&keys.get(index).unwrap()).unwrap())
+                self.data().token_metadata_by_id.get(&keys.get(index).unwrap()).unwrap())
         ).collect::<HashMap<_,_>>()
     }
 
@@ -104,7 +84,7 @@ impl Contract {
     pub fn list_miners_by_owner_and_type(&self, owner_id: ValidAccountId, type_id: TokenMetadataId,
         from_index: u64, limit: u64
     ) ->HashMap<TokenId, Token> {
-        let all_tokens = self.miners_per_owner.get(owner_id.as_ref())
+        let all_tokens = self.data().miners_per_owner.get(owner_id.as_ref())
             .unwrap_or(UnorderedMap::new(unique_prefix(owner_id.as_ref())));
         
         let tokens_of_this_type = all_tokens.get(&type_id)
@@ -115,14 +95,14 @@ impl Contract {
         (from_index..std::cmp::min(from_index + limit, tokenids.len() as u64)).map(
             |index| (
                 (*tokenids.get(index as usize).unwrap()).clone(), 
-                self.miners_by_id.get(tokenids.get(index as usize).unwrap()).unwrap())
+                self.data().miners_by_id.get(tokenids.get(index as usize).unwrap()).unwrap())
         ).collect::<HashMap<_,_>>()
     }
 
     pub fn list_miners_by_onwer(&self, owner_id: ValidAccountId, 
         from_index: u64, limit: u64
     ) ->HashMap<TokenId, Token> {
-        let all_tokens = self.miners_per_owner.get(owner_id.as_ref())
+        let all_tokens = self.data().miners_per_owner.get(owner_id.as_ref())
             .unwrap_or(UnorderedMap::new(unique_prefix(owner_id.as_ref())));
         
         let types = all_tokens.keys_as_vector();
@@ -139,8 +119,30 @@ impl Contract {
         (from_index..std::cmp::min(from_index + limit, retids.len() as u64)).map(
             |index| (
                 (*retids.get(index as usize).unwrap()).clone(), 
-                self.miners_by_id.get(retids.get(index as usize).unwrap()).unwrap())
+                self.data().miners_by_id.get(retids.get(index as usize).unwrap()).unwrap())
         ).collect::<HashMap<_,_>>()
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn test_transfer_powers_to_miner_owner() {
+        let mut contract = setup_contract(accounts(2), 1);
+        contract.issue_power_cards(accounts(1), "power-001".to_string(), power_metadata(5, 100));
+        contract.batch_transfer_powers(accounts(2), "power-001".to_string(), 2);
+
+        // powers of the receiver don't clash with its miners
+        assert_eq!(miners_of(&contract, accounts(2)), vec!["mid-001#0".to_string()]);
+        call_with(accounts(2), 0);
+        contract.consume_powers_by_tokens("power-001".to_string(), vec!["mid-001#0".to_string()]);
+        assert_eq!(contract.nft_token("mid-001#0".to_string()).unwrap().power_left, 100);
+        let powers = contract.data().powers_per_owner.get(accounts(2).as_ref()).unwrap();
+        assert_eq!(powers.get(&"power-001".to_string()), Some(1));
+    }
+}
@@ -29,6 +29,17 @@ pub(crate) fn unique_power_prefix(account_id: &AccountId) -> Vec<u8> {
     prefix
 }
 
+pub(crate) fn unique_operator_prefix(account_id: &AccountId) -> Vec<u8> {
+    let mut prefix = Vec::with_capacity(33);
+    prefix.push(b'O');
+    prefix.extend(env::sha256(account_id.as_bytes()));
+    prefix
+}
+
+pub(crate) fn unique_role_prefix(role: Role) -> Vec<u8> {
+    vec![b'r', role as u8]
+}
+
 pub(crate) fn assert_one_yocto() {
     assert_eq!(
         env::attached_deposit(),
@@ -79,25 +90,42 @@ pub(crate) fn refund_approved_account_ids(
     Promise::new(account_id).transfer(Balance::from(storage_released) * STORAGE_PRICE_PER_BYTE)
 }
 
+/// return deposit of storage freed to the account that paid for it
+pub(crate) fn refund_released_storage(account_id: AccountId, storage_released: u64) {
+    if storage_released > 0 {
+        Promise::new(account_id).transfer(Balance::from(storage_released) * STORAGE_PRICE_PER_BYTE);
+    }
+}
+
 
 impl Contract {
 
     pub(crate) fn assert_owner(&self) {
         assert_eq!(
             &env::predecessor_account_id(),
-            &self.owner_id,
+            &self.data().owner_id,
             "Owner's method"
         );
     }
 
+    /// operator is the owner itself, the renter while the miner is leased,
+    /// or an account the owner delegated to, in which case owner keeps control too.
+    pub(crate) fn assert_miner_control(&self, token_id: &TokenId, miner: &Token, account_id: &AccountId) {
+        if &miner.operator != account_id
+            && !(&miner.owner_id == account_id && self.data().leases.get(token_id).is_none())
+        {
+            env::panic("No control of this miner.".as_bytes())
+        }
+    }
+
     pub(crate) fn internal_add_token_to_owner(
         &mut self,
         account_id: &AccountId,
         token_id: &TokenId,
     ) {
-        let token = self.miners_by_id.get(token_id).expect("Internal Error:");
+        let token = self.data().miners_by_id.get(token_id).expect("Internal Error:");
 
-        let mut miner_metadata_map = self
+        let mut miner_metadata_map = self.data()
             .miners_per_owner
             .get(account_id)
             .unwrap_or(UnorderedMap::new(unique_prefix(account_id)));
@@ -107,8 +135,8 @@ impl Contract {
             .unwrap_or_else(|| UnorderedSet::new(unique_prefix_for_owner_token(account_id, &token.miner_metadata_id)));
         
         tokens_set.insert(token_id);
-        miner_metadata_map.insert(account_id, &tokens_set);
-        self.miners_per_owner.insert(account_id, &miner_metadata_map);
+        miner_metadata_map.insert(&token.miner_metadata_id, &tokens_set);
+        self.data_mut().miners_per_owner.insert(account_id, &miner_metadata_map);
     }
 
     pub(crate) fn internal_remove_token_from_owner(
@@ -116,9 +144,9 @@ impl Contract {
         account_id: &AccountId,
         token_id: &TokenId,
     ) {
-        let token = self.miners_by_id.get(token_id).expect("Internal Error:");
+        let token = self.data().miners_by_id.get(token_id).expect("Internal Error:");
 
-        let mut miner_metadata_map = self
+        let mut miner_metadata_map = self.data()
             .miners_per_owner
             .get(account_id)
             .expect("Token should be owned by the sender");
@@ -132,12 +160,70 @@ impl Contract {
             miner_metadata_map.insert(&token.miner_metadata_id, &tokens_set);
         }
         if miner_metadata_map.is_empty() {
-            self.miners_per_owner.remove(account_id);
+            self.data_mut().miners_per_owner.remove(account_id);
         } else {
-            self.miners_per_owner.insert(account_id, &miner_metadata_map);
+            self.data_mut().miners_per_owner.insert(account_id, &miner_metadata_map);
         }
     }
 
+    /// credit power cards to the account
+    pub(crate) fn internal_add_powers(&mut self, account_id: &AccountId, card: &TokenMetadataId, count: u32) {
+        let mut power_map = self.data().powers_per_owner.get(account_id)
+            .unwrap_or_else(|| UnorderedMap::new(unique_power_prefix(account_id)));
+        let num_has = power_map.get(card).unwrap_or(0);
+        power_map.insert(card, &(num_has + count));
+        self.data_mut().powers_per_owner.insert(account_id, &power_map);
+    }
+
+    /// debit power cards from the account, panics if it doesn't have enough
+    pub(crate) fn internal_remove_powers(&mut self, account_id: &AccountId, card: &TokenMetadataId, count: u32) {
+        let mut power_map = self.data().powers_per_owner.get(account_id)
+            .expect("Insufficient power cards.");
+        let num_has = power_map.get(card).unwrap_or(0);
+        if num_has < count {
+            env::panic("Insufficient power cards.".as_bytes());
+        }
+        if num_has > count {
+            power_map.insert(card, &(num_has - count));
+            self.data_mut().powers_per_owner.insert(account_id, &power_map);
+        } else {
+            power_map.remove(card);
+            if power_map.is_empty() {
+                self.data_mut().powers_per_owner.remove(account_id);
+            } else {
+                self.data_mut().powers_per_owner.insert(account_id, &power_map);
+            }
+        }
+    }
+
+    /// power each card of the type adds to a miner
+    pub(crate) fn get_card_power(&self, card: &TokenMetadataId) -> u32 {
+        let metadata = self.data().token_metadata_by_id.get(card).expect("Power card doesn't exist");
+        let power_metadata: PowerMetadata = near_sdk::serde_json::from_str(
+            &metadata.extra.expect("extra msg illegal!")
+        ).expect("extra msg illegal!");
+        power_metadata.power
+    }
+
+    /// add power to the miner, a running miner consumes it at once
+    /// and its power deadline is extended by the epochs it covers
+    pub(crate) fn internal_apply_power(&mut self, token_id: &TokenId, power: u32) {
+        let mut miner = self.data().miners_by_id.get(token_id).expect("Miner doesn't exist");
+        miner.power_left += power;
+        if miner.status == ST_NORMAL && miner.switch == PW_ON {
+            let metadata = self.data().miner_metadata_by_id.get(&miner.miner_metadata_id)
+                .expect("Internal Error: no miner_metadata of this miner");
+            let hours = miner.power_left / metadata.w;
+            if hours > 0 {
+                self.internal_remove_from_power_event(token_id, &miner.power_deadline);
+                miner.power_left -= hours * metadata.w;
+                miner.power_deadline += hours;
+                self.internal_add_to_power_event(token_id, &miner.power_deadline);
+            }
+        }
+        self.data_mut().miners_by_id.insert(token_id, &miner);
+    }
+
     pub(crate) fn internal_transfer(
         &mut self,
         sender_id: &AccountId,
@@ -146,18 +232,20 @@ impl Contract {
         enforce_owner_id: Option<&ValidAccountId>,
         memo: Option<String>,
     ) -> (AccountId, HashSet<AccountId>) {
+        self.assert_not_paused(PAUSE_TRANSFER);
+        self.assert_not_leased(token_id);
         let Token {
             sn,
             owner_id,
             metadata_id,
             miner_metadata_id,
-            operator,
+            operator: _,
             switch,
             status,
             power_left,
             power_deadline,
             approved_account_ids,
-        } = self.miners_by_id.get(token_id).expect("Token not found");
+        } = self.data().miners_by_id.get(token_id).expect("Token not found");
         if sender_id != &owner_id && !approved_account_ids.contains(sender_id) {
             env::panic(b"Unauthorized");
         }
@@ -183,22 +271,28 @@ impl Contract {
             .as_bytes(),
         );
 
+        // delegation to operator ends with ownership
+        let mut miner = self.data().miners_by_id.get(token_id).unwrap();
+        self.internal_set_operator(token_id, &mut miner, &owner_id);
+
         self.internal_remove_token_from_owner(&owner_id, token_id);
         self.internal_add_token_to_owner(receiver_id, token_id);
+        self.internal_remove_listing(token_id);
 
         let token = Token {
             sn,
             owner_id: receiver_id.clone(),
             metadata_id,
             miner_metadata_id,
-            operator,
+            // control goes with ownership
+            operator: receiver_id.clone(),
             switch,
             status,
             power_left,
             power_deadline,
             approved_account_ids: Default::default(),
         };
-        self.miners_by_id.insert(token_id, &token);
+        self.data_mut().miners_by_id.insert(token_id, &token);
 
         if let Some(memo) = memo {
             env::log(format!("Memo: {}", memo).as_bytes());
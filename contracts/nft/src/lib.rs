@@ -2,17 +2,27 @@ use std::collections::{HashSet, HashMap};
 
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::{LookupMap, UnorderedMap, UnorderedSet};
-use near_sdk::json_types::{ValidAccountId, Base64VecU8, U64};
+use near_sdk::json_types::{ValidAccountId, Base64VecU8, U64, U128};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{env, near_bindgen, AccountId, Balance, PanicOnDefault, Promise, BlockHeight};
 
 use crate::internal::*;
+use crate::upgrade::ContractDataV2;
 pub use crate::mint::*;
 pub use crate::nft_core::*;
 pub use crate::custom::*;
 pub use crate::mining::*;
 pub use crate::mining_internal::*;
-use crate::nft_metadata::{TokenMetadata, MinerMetadata};
+pub use crate::config::*;
+pub use crate::roles::*;
+pub use crate::pause::*;
+pub use crate::market::*;
+pub use crate::token_receiver::*;
+pub use crate::sale::*;
+pub use crate::compound::*;
+pub use crate::auction::*;
+pub use crate::rental::*;
+use crate::nft_metadata::{TokenMetadata, MinerMetadata, PowerMetadata};
 
 mod internal;
 mod mint;
@@ -21,6 +31,20 @@ mod nft_metadata;
 mod custom;
 mod mining;
 mod mining_internal;
+mod config;
+mod owner;
+mod roles;
+mod pause;
+mod upgrade;
+mod market;
+mod token_receiver;
+mod sale;
+mod compound;
+mod auction;
+mod rental;
+mod operator;
+#[cfg(test)]
+mod test_utils;
 
 #[global_allocator]
 static ALLOC: near_sdk::wee_alloc::WeeAlloc<'_> = near_sdk::wee_alloc::WeeAlloc::INIT;
@@ -62,11 +86,17 @@ pub struct Token {
     pub approved_account_ids: HashSet<AccountId>,
 }
 
-#[near_bindgen]
-#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
-pub struct Contract {
+/// all state of this contract, wrapped in VersionedContractData
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractData {
     /// owner of this contract
     pub owner_id: AccountId,
+    /// account proposed by owner to take over ownership, waiting for its acceptance
+    pub proposed_owner_id: Option<AccountId>,
+    /// role -> accounts granted with that role
+    pub roles: LookupMap<Role, UnorderedSet<AccountId>>,
+    /// paused features, see PAUSE_* flags
+    pub paused: PauseFlags,
 
     //*********************
     //  NFT PARTS
@@ -95,9 +125,19 @@ pub struct Contract {
     //        MINING PARTS
     // *****************************************
     
-    /// each epoch, we distribute epoch_award to some miner or pool
+    /// each epoch, we distribute award to some miner or pool,
+    /// the award is epoch_award at award_base_epoch and halves every halving_interval epochs after it,
+    /// halving_interval 0 means never halve.
+    /// Changing award or interval re-anchors the schedule at the epoch it takes effect.
     pub current_mining_epoch: MiningEpoch,
     pub epoch_award: Balance,
+    pub halving_interval: MiningEpoch,
+    pub award_base_epoch: MiningEpoch,
+
+    /// optional upper limit of total award, None means unlimited
+    pub emission_cap: Option<Balance>,
+    /// total award distributed so far
+    pub total_emission: Balance,
     
     /// we can only settle epoch after interval blocks from previous epoch
     pub min_interval_of_epoch: BlockHeight,
@@ -115,15 +155,71 @@ pub struct Contract {
     /// mining pool supports
     pub mining_pools: UnorderedMap<AccountId, MiningPool>,
 
+    /// param changes queued by owner, applied after param_timelock epochs
+    pub param_timelock: MiningEpoch,
+    pub pending_param_changes: UnorderedMap<u64, ParamChange>,
+    pub next_param_change_id: u64,
+
+    // *****************************************
+    //        MARKET PARTS
+    // *****************************************
+
+    /// vBTC contract whose tokens are accepted in ft_on_transfer
+    pub vbtc_token_id: Option<AccountId>,
+    /// miners for sale at fixed price
+    pub listings: UnorderedMap<TokenId, Listing>,
+
+    /// miner type -> primary sale of new miners of that type
+    pub sale_campaigns: UnorderedMap<MinerMetadataId, SaleCampaign>,
+    /// (miner type, buyer) -> miners bought in sale campaigns
+    pub sale_purchases: LookupMap<(MinerMetadataId, AccountId), u32>,
+    /// power card -> vBTC price of each card
+    pub power_card_prices: UnorderedMap<TokenMetadataId, Balance>,
+    /// vBTC received from power card sales and not withdrawn yet,
+    /// other vBTC held by this contract is escrowed bids
+    pub vbtc_revenue: Balance,
+    /// user -> vBTC paid to it by this contract but failed in transfer, claimable later
+    pub failed_vbtc_payouts: LookupMap<AccountId, Balance>,
+    /// user -> how its award is turned into power cards in settlement
+    pub auto_compounds: LookupMap<AccountId, AutoCompound>,
+    /// miners in auction, escrowed in this contract
+    pub auctions: UnorderedMap<TokenId, Auction>,
+    /// miners offered for rent and miners leased
+    pub rental_offers: UnorderedMap<TokenId, RentalOffer>,
+    pub leases: UnorderedMap<TokenId, Lease>,
+    /// epoch -> miners whose lease expires in that epoch
+    pub lease_expiries: LookupMap<MiningEpoch, UnorderedSet<TokenId>>,
+    /// operator -> miners delegated to it by owners
+    pub miners_per_operator: LookupMap<AccountId, UnorderedSet<TokenId>>,
+}
+
+/// versioned contract data, so that state layout can evolve with upgrades,
+/// see migrate for how old layouts are converted.
+/// Released layouts are kept in front of Current, so their tags stay the same.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[allow(clippy::large_enum_variant)]
+pub enum VersionedContractData {
+    V2(ContractDataV2),
+    Current(ContractData),
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct Contract {
+    data: VersionedContractData,
 }
 
 #[near_bindgen]
 impl Contract {
 
     #[init]
-    pub fn new(owner_id: ValidAccountId) -> Self {
+    pub fn new(owner_id: ValidAccountId,
+        epoch_award: Option<U128>,
+        halving_interval: Option<MiningEpoch>,
+        emission_cap: Option<U128>,
+    ) -> Self {
         assert!(!env::state_exists(), "Already initialized");
-        Self {
+        Self { data: VersionedContractData::Current(ContractData {
             miners_per_owner: LookupMap::new(b"a".to_vec()),
             powers_per_owner: LookupMap::new(b"b".to_vec()),
 
@@ -132,15 +228,55 @@ impl Contract {
             miner_metadata_by_id: UnorderedMap::new(b"e".to_vec()),
             
             owner_id: owner_id.into(),
+            proposed_owner_id: None,
+            roles: LookupMap::new(b"j".to_vec()),
+            paused: 0,
             
             current_mining_epoch: 0,
             current_epoch_start_at: env::block_index(),
             current_total_thash: 0,
-            epoch_award: 2500000000,
+            epoch_award: epoch_award.map(|v| v.into()).unwrap_or(DEFAULT_EPOCH_AWARD),
+            halving_interval: halving_interval.unwrap_or(DEFAULT_HALVING_INTERVAL),
+            award_base_epoch: 0,
+            emission_cap: emission_cap.map(|v| v.into()),
+            total_emission: 0,
             min_interval_of_epoch: 3600,
             mining_pools: UnorderedMap::new(b"f".to_vec()),
             mining_entities: UnorderedMap::new(b"g".to_vec()),
-            power_events: LookupMap::new(b"h".to_vec())
+            power_events: LookupMap::new(b"h".to_vec()),
+            param_timelock: DEFAULT_PARAM_TIMELOCK,
+            pending_param_changes: UnorderedMap::new(b"i".to_vec()),
+            next_param_change_id: 0,
+            vbtc_token_id: None,
+            listings: UnorderedMap::new(b"k".to_vec()),
+            sale_campaigns: UnorderedMap::new(b"l".to_vec()),
+            sale_purchases: LookupMap::new(b"m".to_vec()),
+            power_card_prices: UnorderedMap::new(b"n".to_vec()),
+            vbtc_revenue: 0,
+            failed_vbtc_payouts: LookupMap::new(b"A".to_vec()),
+            auto_compounds: LookupMap::new(b"o".to_vec()),
+            auctions: UnorderedMap::new(b"p".to_vec()),
+            rental_offers: UnorderedMap::new(b"q".to_vec()),
+            leases: UnorderedMap::new(b"s".to_vec()),
+            lease_expiries: LookupMap::new(b"t".to_vec()),
+            miners_per_operator: LookupMap::new(b"u".to_vec()),
+        }) }
+    }
+}
+
+impl Contract {
+
+    pub(crate) fn data(&self) -> &ContractData {
+        match &self.data {
+            VersionedContractData::Current(data) => data,
+            _ => env::panic(b"State needs migrate"),
+        }
+    }
+
+    pub(crate) fn data_mut(&mut self) -> &mut ContractData {
+        match &mut self.data {
+            VersionedContractData::Current(data) => data,
+            _ => env::panic(b"State needs migrate"),
         }
     }
 }
@@ -0,0 +1,172 @@
+use crate::*;
+
+/// what a listed miner is paid with
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Currency {
+    Near,
+    /// paid through ft_on_transfer of vbtc_token_id
+    Vbtc,
+}
+
+/// a miner for sale at fixed price,
+/// it is removed when the miner is sold or transferred in any other way.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Listing {
+    pub owner_id: AccountId,
+    pub miner_metadata_id: MinerMetadataId,
+    pub price: U128,
+    pub currency: Currency,
+}
+
+#[near_bindgen]
+impl Contract {
+
+    /// list a miner for sale, listing it again updates price and currency.
+    /// Caller must attach deposit to cover storage of the listing, the rest is refunded.
+    #[payable]
+    pub fn list_miner(&mut self, token_id: TokenId, price: U128, currency: Currency) {
+        self.assert_not_paused(PAUSE_TRANSFER);
+        let initial_storage_usage = env::storage_usage();
+        let owner_id = env::predecessor_account_id();
+        let miner = self.data().miners_by_id.get(&token_id).expect("Miner doesn't exist");
+        assert_eq!(miner.owner_id, owner_id, "Only owner can list this miner");
+        self.assert_not_leased(&token_id);
+        assert_eq!(miner.switch, PW_OFF, "Miner should be powered off before listed");
+        assert!(price.0 > 0, "Price should be a positive number");
+        if currency == Currency::Vbtc {
+            self.assert_vbtc_token_set();
+        }
+
+        self.data_mut().listings.insert(&token_id, &Listing {
+            owner_id: owner_id.clone(),
+            miner_metadata_id: miner.miner_metadata_id,
+            price,
+            currency,
+        });
+        env::log(
+            format!(
+                "List {} by @{} at {} {:?}.", token_id, owner_id, price.0, currency
+            ).as_bytes());
+        deposit_refund(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    pub fn cancel_listing(&mut self, token_id: TokenId) {
+        let listing = self.data().listings.get(&token_id).expect("Listing doesn't exist");
+        assert_eq!(listing.owner_id, env::predecessor_account_id(), "Only owner can cancel this listing");
+        self.internal_remove_listing(&token_id);
+        env::log(format!("Cancel listing of {} by @{}.", token_id, listing.owner_id).as_bytes());
+    }
+
+    /// buy a miner listed in NEAR, attached deposit beyond price is refunded
+    #[payable]
+    pub fn buy_miner(&mut self, token_id: TokenId) {
+        let listing = self.data().listings.get(&token_id).expect("Listing doesn't exist");
+        assert_eq!(listing.currency, Currency::Near, "This miner is not sold in NEAR");
+        let price: Balance = listing.price.into();
+        let attached_deposit = env::attached_deposit();
+        assert!(attached_deposit >= price, "Must attach {} yoctoⓃ to buy this miner", price);
+
+        let buyer_id = env::predecessor_account_id();
+        self.internal_buy_miner(&token_id, &buyer_id);
+        Promise::new(listing.owner_id).transfer(price);
+        if attached_deposit > price {
+            Promise::new(buyer_id).transfer(attached_deposit - price);
+        }
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    pub fn get_listing(&self, token_id: TokenId) -> Option<Listing> {
+        self.data().listings.get(&token_id)
+    }
+
+    /// list miners for sale, only of the miner type if given.
+    /// from_index and limit page over all listings, those of other types in the page are skipped.
+    pub fn list_listings(&self, miner_type: Option<MinerMetadataId>,
+        from_index: u64, limit: u64
+    ) -> HashMap<TokenId, Listing> {
+        let keys = self.data().listings.keys_as_vector();
+
+        (from_index..std::cmp::min(from_index + limit, keys.len())).map(
+            |index| (
+                keys.get(index).unwrap(),
+                self.data().listings.get(&keys.get(index).unwrap()).unwrap())
+        ).filter(|(_, listing)| match &miner_type {
+            Some(miner_type) => miner_type == &listing.miner_metadata_id,
+            None => true,
+        }).collect::<HashMap<_,_>>()
+    }
+}
+
+impl Contract {
+
+    /// transfer listed miner to buyer, payment is handled by caller
+    pub(crate) fn internal_buy_miner(&mut self, token_id: &TokenId, buyer_id: &AccountId) {
+        let listing = self.data().listings.get(token_id).expect("Listing doesn't exist");
+        let miner = self.data().miners_by_id.get(token_id).expect("Miner doesn't exist");
+        assert_eq!(miner.switch, PW_OFF, "Miner should be powered off before sold");
+        let (previous_owner_id, approved_account_ids) = self.internal_transfer(
+            &listing.owner_id, buyer_id, token_id, None, None,
+        );
+        refund_approved_account_ids(previous_owner_id, &approved_account_ids);
+        env::log(
+            format!(
+                "Sell {} from @{} to @{} at {} {:?}.",
+                token_id, listing.owner_id, buyer_id, listing.price.0, listing.currency
+            ).as_bytes());
+    }
+
+    /// called whenever the miner changes owner,
+    /// storage deposit of the listing is returned to its owner
+    pub(crate) fn internal_remove_listing(&mut self, token_id: &TokenId) {
+        let initial_storage_usage = env::storage_usage();
+        if let Some(listing) = self.data_mut().listings.remove(token_id) {
+            refund_released_storage(
+                listing.owner_id, initial_storage_usage.saturating_sub(env::storage_usage()),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn test_list_and_buy_miner() {
+        let mut contract = setup_contract(accounts(2), 2);
+        let token_id = "mid-001#0".to_string();
+
+        call_with(accounts(2), ONE_NEAR);
+        contract.list_miner(token_id.clone(), (5 * ONE_NEAR).into(), Currency::Near);
+        assert_eq!(contract.list_listings(Some("mid-001".to_string()), 0, 10).len(), 1);
+
+        call_with(accounts(3), 6 * ONE_NEAR);
+        contract.buy_miner(token_id.clone());
+        assert!(contract.get_listing(token_id.clone()).is_none());
+        assert_eq!(contract.nft_token(token_id.clone()).unwrap().owner_id, accounts(3).to_string());
+        assert_eq!(miners_of(&contract, accounts(3)), vec![token_id]);
+        assert_eq!(miners_of(&contract, accounts(2)), vec!["mid-001#1".to_string()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Miner is leased.")]
+    fn test_list_leased_miner() {
+        let mut contract = setup_contract(accounts(2), 1);
+        let token_id = "mid-001#0".to_string();
+        call_with(accounts(2), ONE_NEAR);
+        contract.offer_rental(token_id.clone(), ONE_NEAR.into(), 10);
+        call_with(accounts(3), 2 * ONE_NEAR);
+        contract.rent_miner(token_id.clone(), 1);
+
+        call_with(accounts(2), ONE_NEAR);
+        contract.list_miner(token_id, ONE_NEAR.into(), Currency::Near);
+    }
+}
@@ -6,6 +6,11 @@ pub type Thash = u32;
 /// 0 - normal, 1 - maintaining
 pub type PoolStatus = u8;
 
+/// 25 BTC in satoshi, the award of each epoch before first halving
+pub const DEFAULT_EPOCH_AWARD: Balance = 2500000000;
+/// same as bitcoin, award halves every 210000 epochs
+pub const DEFAULT_HALVING_INTERVAL: MiningEpoch = 210000;
+
 #[derive(BorshDeserialize, BorshSerialize)]
 pub struct MiningPool {
     pub owner_id: AccountId,
@@ -27,80 +32,93 @@ pub struct HumanReadableMiningPool {
     pub miners: Vec<TokenId>,
 }
 
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableEmission {
+    pub current_mining_epoch: MiningEpoch,
+    pub initial_award: U128,
+    /// epoch where initial_award starts to halve from
+    pub award_base_epoch: MiningEpoch,
+    pub halving_interval: MiningEpoch,
+    pub current_award: U128,
+    /// None if award never halves
+    pub next_halving_epoch: Option<MiningEpoch>,
+    pub next_halving_award: U128,
+    pub emission_cap: Option<U128>,
+    pub total_emission: U128,
+}
+
 
 #[near_bindgen]
 impl Contract {
 
     pub fn settle_mining_epoch(&mut self) {
 
-        self.assert_owner();
+        self.assert_role(Role::Settler);
+        self.assert_not_paused(PAUSE_SETTLEMENT);
 
-        if env::block_index() < self.current_epoch_start_at + self.min_interval_of_epoch {
+        if env::block_index() < self.data().current_epoch_start_at + self.data().min_interval_of_epoch {
             env::panic("not long from last settlement.".as_bytes())
         }
-        self.current_epoch_start_at = env::block_index();
+        self.data_mut().current_epoch_start_at = env::block_index();
 
         let value = self.make_random_value();
 
         let block_producer = self.find_block_producer(value);
 
+        let award = self.get_epoch_award();
+        self.data_mut().total_emission += award;
+        let award = self.settle_auto_compound(&block_producer, award);
+
         env::log(
             format!(
-                "Send vBTC to {} in epoch {}.", block_producer.clone(), self.current_mining_epoch
+                "Send {} vBTC to {} in epoch {}.", award, block_producer.clone(), self.data().current_mining_epoch
             ).as_bytes());
 
-        self.current_mining_epoch += 1;
+        self.data_mut().current_mining_epoch += 1;
+        self.settle_param_changes();
         self.settle_power_for_individuals();
+        self.settle_leases();
         self.settle_power_for_pools();
         self.settle_random_failures();
     }
 
     pub fn batch_poweron_miners(&mut self, token_ids: Vec<TokenId>,) {
-        let owner_id = env::predecessor_account_id();
+        self.assert_not_paused(PAUSE_POWER);
+        let operator = env::predecessor_account_id();
         for token_id in token_ids.iter() {
-            let mut miner = self.miners_by_id.get(token_id).expect("Miner doesn't exist");
-            if miner.owner_id != owner_id || miner.owner_id != miner.operator {
-                env::panic("No control of this miner.".as_bytes())
-            }
-            let metadata = self.miner_metadata_by_id.get(&miner.miner_metadata_id)
+            let mut miner = self.data().miners_by_id.get(token_id).expect("Miner doesn't exist");
+            self.assert_miner_control(token_id, &miner, &operator);
+            let metadata = self.data().miner_metadata_by_id.get(&miner.miner_metadata_id)
                 .expect("Internal Error: no miner_metadata of this miner");
             if miner.status == ST_NORMAL && miner.switch != PW_ON {
                 miner.switch = PW_ON;
                 // update total thash
-                self.internal_increase_thash(&miner.owner_id, &metadata);
+                let beneficiary = self.get_miner_beneficiary(token_id, &miner);
+                self.internal_increase_thash(&beneficiary, &metadata);
                 // consume power
                 let (used, mining_epoch) = self.get_power_consume(miner.power_left, &metadata);
                 if used == 0 {
                     env::panic("Not enough power to use.".as_bytes())
                 }
                 miner.power_left -= used;
+                miner.power_deadline = mining_epoch;
                 // udapte power events
                 self.internal_add_to_power_event(&token_id, &mining_epoch);
                 // udpate miner itself
-                self.miners_by_id.insert(&token_id, &miner);
+                self.data_mut().miners_by_id.insert(&token_id, &miner);
             }
         }
     }
 
     pub fn batch_poweroff_miners(&mut self, token_ids: Vec<TokenId>,) {
-        let owner_id = env::predecessor_account_id();
+        self.assert_not_paused(PAUSE_POWER);
+        let operator = env::predecessor_account_id();
         for token_id in token_ids.iter() {
-            let mut miner = self.miners_by_id.get(token_id).expect("Miner doesn't exist");
-            if miner.owner_id != owner_id || miner.owner_id != miner.operator {
-                env::panic("No control of this miner.".as_bytes())
-            }
-            let metadata = self.miner_metadata_by_id.get(&miner.miner_metadata_id)
-                .expect("Internal Error: no miner_metadata of this miner");
+            let mut miner = self.data().miners_by_id.get(token_id).expect("Miner doesn't exist");
+            self.assert_miner_control(token_id, &miner, &operator);
             if miner.status == ST_NORMAL && miner.switch != PW_OFF {
-                miner.switch = PW_OFF;
-                // update total thash
-                self.internal_reduce_thash(&miner.owner_id, &metadata);
-                // update power events
-                self.internal_remove_from_power_event(&token_id, &miner.power_deadline);
-                // refund power
-                miner.power_left += self.get_power_refund(miner.power_deadline - self.current_mining_epoch, &metadata);
-                // udpate miner itself
-                self.miners_by_id.insert(&token_id, &miner);
+                self.internal_power_off(token_id, &mut miner);
             }
         }
     }
@@ -120,4 +138,27 @@ impl Contract {
 
     }
 
-}
\ No newline at end of file
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    /// current award and halving schedule
+    pub fn get_emission_info(&self) -> HumanReadableEmission {
+        let next_halving_epoch = self.get_next_halving_epoch();
+        let next_halving_award = next_halving_epoch
+            .map(|epoch| self.get_scheduled_award(epoch))
+            .unwrap_or_else(|| self.get_scheduled_award(self.data().current_mining_epoch));
+        HumanReadableEmission {
+            current_mining_epoch: self.data().current_mining_epoch,
+            initial_award: self.data().epoch_award.into(),
+            award_base_epoch: self.data().award_base_epoch,
+            halving_interval: self.data().halving_interval,
+            current_award: self.get_epoch_award().into(),
+            next_halving_epoch,
+            next_halving_award: next_halving_award.into(),
+            emission_cap: self.data().emission_cap.map(|v| v.into()),
+            total_emission: self.data().total_emission.into(),
+        }
+    }
+
+}
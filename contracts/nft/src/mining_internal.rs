@@ -16,34 +16,66 @@ impl Contract {
         let ptr: *const u128 = ptr as *const u128;
         let big_rand: u128 = unsafe { *ptr };
 
-        let value = U256::from(self.current_total_thash) * U256::from(big_rand) 
+        let value = U256::from(self.data().current_total_thash) * U256::from(big_rand) 
             / (U256::from(u128::max_value()) + U256::from(1));
         
-        env::log(format!("Random number is {} in epoch {}.", value.as_u128(), self.current_mining_epoch).as_bytes());
+        env::log(format!("Random number is {} in epoch {}.", value.as_u128(), self.data().current_mining_epoch).as_bytes());
 
         value.as_u128() as Thash
     }
 
     pub(crate) fn find_block_producer(&self, value: Thash) -> AccountId {
-        let keys = self.mining_entities.keys_as_vector();
+        let keys = self.data().mining_entities.keys_as_vector();
         let mut border: Thash = 0;
-        let mut ret = self.owner_id.clone();
+        let mut ret = self.data().owner_id.clone();
         for index in 0..keys.len() {
             let entity = keys.get(index).unwrap();
-            let thash = self.mining_entities.get(&entity).unwrap();
+            let thash = self.data().mining_entities.get(&entity).unwrap();
             border += thash;
             if border > value {
                 ret = entity;
                 break;
             }
         }
-        env::log(format!("{} produced rbtc block in {}.", ret, self.current_mining_epoch).as_bytes());
+        env::log(format!("{} produced rbtc block in {}.", ret, self.data().current_mining_epoch).as_bytes());
         ret
     }
 
+    /// award of the given epoch by halving schedule, emission cap not counted in
+    pub(crate) fn get_scheduled_award(&self, epoch: MiningEpoch) -> Balance {
+        if self.data().halving_interval == 0 {
+            return self.data().epoch_award;
+        }
+        let halvings = epoch.saturating_sub(self.data().award_base_epoch) / self.data().halving_interval;
+        if halvings >= 128 {
+            0
+        } else {
+            self.data().epoch_award >> halvings
+        }
+    }
+
+    /// award of current epoch, limited by what is left under emission cap
+    pub(crate) fn get_epoch_award(&self) -> Balance {
+        let award = self.get_scheduled_award(self.data().current_mining_epoch);
+        match self.data().emission_cap {
+            Some(cap) => std::cmp::min(award, cap.saturating_sub(self.data().total_emission)),
+            None => award,
+        }
+    }
+
+    pub(crate) fn get_next_halving_epoch(&self) -> Option<MiningEpoch> {
+        if self.data().halving_interval == 0 {
+            return None;
+        }
+        let interval = self.data().halving_interval;
+        let base = self.data().award_base_epoch;
+        ((self.data().current_mining_epoch - base) / interval + 1).checked_mul(interval)
+            .and_then(|epochs| base.checked_add(epochs))
+    }
+
     pub(crate) fn get_power_consume(&self, power_left: u32, metadata: &MinerMetadata) -> (u32, MiningEpoch) {
         let hours = power_left / metadata.w;
-        (hours * metadata.w, self.current_mining_epoch + hours)
+        (hours * metadata.w, self.data().current_mining_epoch + hours)
     }
 
     pub(crate) fn get_power_refund(&self, epoch_diff: u32, metadata: &MinerMetadata) -> u32 {
@@ -52,37 +84,53 @@ impl Contract {
     }
 
     pub(crate) fn internal_increase_thash(&mut self, owner_id: &AccountId, metadata: &MinerMetadata) {
-        self.current_total_thash += metadata.thash;
-        let owner_thash = self.mining_entities.get(owner_id).unwrap_or(0);
-        self.mining_entities.insert(owner_id, &(owner_thash + metadata.thash));
+        self.data_mut().current_total_thash += metadata.thash;
+        let owner_thash = self.data().mining_entities.get(owner_id).unwrap_or(0);
+        self.data_mut().mining_entities.insert(owner_id, &(owner_thash + metadata.thash));
     }
 
     pub(crate) fn internal_reduce_thash(&mut self, owner_id: &AccountId, metadata: &MinerMetadata) {
         // update total thash
-        self.current_total_thash -= metadata.thash;
-        let owner_thash = self.mining_entities.get(owner_id).expect("Internal Error: no this mining entity");
+        self.data_mut().current_total_thash -= metadata.thash;
+        let owner_thash = self.data().mining_entities.get(owner_id).expect("Internal Error: no this mining entity");
         let thash_leftover = owner_thash - metadata.thash;
         if thash_leftover > 0 {
-            self.mining_entities.insert(owner_id, &thash_leftover);
+            self.data_mut().mining_entities.insert(owner_id, &thash_leftover);
         } else {
-            self.mining_entities.remove(owner_id);
+            self.data_mut().mining_entities.remove(owner_id);
         }
     }
 
+    /// switch off a running miner and refund power of epochs not mined yet
+    pub(crate) fn internal_power_off(&mut self, token_id: &TokenId, miner: &mut Token) {
+        let metadata = self.data().miner_metadata_by_id.get(&miner.miner_metadata_id)
+            .expect("Internal Error: no miner_metadata of this miner");
+        miner.switch = PW_OFF;
+        // update total thash
+        let beneficiary = self.get_miner_beneficiary(token_id, miner);
+        self.internal_reduce_thash(&beneficiary, &metadata);
+        // update power events
+        self.internal_remove_from_power_event(token_id, &miner.power_deadline);
+        // refund power
+        miner.power_left += self.get_power_refund(miner.power_deadline - self.data().current_mining_epoch, &metadata);
+        // udpate miner itself
+        self.data_mut().miners_by_id.insert(token_id, miner);
+    }
+
     pub(crate) fn internal_add_to_power_event(&mut self, token_id: &TokenId, deadline: &MiningEpoch) {
-        let mut miners_set = self.power_events.get(deadline)
+        let mut miners_set = self.data().power_events.get(deadline)
             .unwrap_or(UnorderedSet::new(format!("w{}", deadline).as_bytes().to_vec()));
         miners_set.insert(&token_id);
-        self.power_events.insert(deadline, &miners_set);
+        self.data_mut().power_events.insert(deadline, &miners_set);
     }
 
     pub(crate) fn internal_remove_from_power_event(&mut self, token_id: &TokenId, deadline: &MiningEpoch) {
-        let mut miner_set = self.power_events.get(deadline).expect("Internal Error: no this power event");
+        let mut miner_set = self.data().power_events.get(deadline).expect("Internal Error: no this power event");
         miner_set.remove(token_id);
         if miner_set.len() > 0 {
-            self.power_events.insert(deadline, &miner_set);
+            self.data_mut().power_events.insert(deadline, &miner_set);
         } else {
-            self.power_events.remove(deadline);
+            self.data_mut().power_events.remove(deadline);
         }
     }
 
@@ -90,19 +138,21 @@ impl Contract {
     /// to update power consume
     pub(crate) fn settle_power_for_individuals(&mut self) {
         env::log(format!("settle_power_for_individuals.").as_bytes());
-        let miners = self.power_events.get(&self.current_mining_epoch)
+        let miners = self.data().power_events.get(&self.data().current_mining_epoch)
             .unwrap_or(UnorderedSet::new(b"non-relevant".to_vec()));
         for token_id in miners.iter() {
-            let mut miner = self.miners_by_id.get(&token_id).expect("Internal Error: Miner not exist.");
+            let mut miner = self.data().miners_by_id.get(&token_id).expect("Internal Error: Miner not exist.");
             miner.switch = PW_OFF;
-            self.miners_by_id.insert(&token_id, &miner);
+            self.data_mut().miners_by_id.insert(&token_id, &miner);
 
-            let miner_metadata: MinerMetadata = self.miner_metadata_by_id.get(&miner.miner_metadata_id)
+            let miner_metadata: MinerMetadata = self.data().miner_metadata_by_id.get(&miner.miner_metadata_id)
                 .expect("Internal Error: No miner_metadata");
 
-            self.internal_reduce_thash(&miner.owner_id, &miner_metadata);
+            let beneficiary = self.get_miner_beneficiary(&token_id, &miner);
+            self.internal_reduce_thash(&beneficiary, &miner_metadata);
         }
-        self.power_events.remove(&self.current_mining_epoch);
+        let current_mining_epoch = self.data().current_mining_epoch;
+        self.data_mut().power_events.remove(&current_mining_epoch);
     }
     
     pub(crate) fn settle_power_for_pools(&mut self) {
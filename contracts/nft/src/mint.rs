@@ -9,21 +9,21 @@ impl Contract {
     pub fn see_gas(&mut self, copies: U64) -> U64 {
         let quantity: u64 = copies.into();
         
-        self.current_total_thash = quantity as u32 * 100;
+        self.data_mut().current_total_thash = quantity as u32 * 100;
         let value = self.make_random_value();
-        let biggest_value = self.current_total_thash as u32 - 1;
+        let biggest_value = self.data().current_total_thash as u32 - 1;
         env::log(
             format!(
                 "random is {}, we take {} as max.", value, biggest_value
             ).as_bytes());
 
-        let keys = self.miner_metadata_by_id.keys_as_vector();
+        let keys = self.data().miner_metadata_by_id.keys_as_vector();
 
         let mut border: Thash = 0;
 
         for index in 0..quantity {
             let non_relevant1 = keys.get(0).unwrap();
-            let non_relevant2 = self.miner_metadata_by_id.get(&String::from("mid-001")).unwrap();
+            let non_relevant2 = self.data().miner_metadata_by_id.get(&String::from("mid-001")).unwrap();
             let thash = 100;
             border += thash;
             if border > biggest_value {
@@ -48,42 +48,24 @@ impl Contract {
         metadata_id: TokenMetadataId, metadata: TokenMetadata
     ) {
         
-        self.assert_owner();
+        self.assert_role(Role::Minter);
+        self.assert_not_paused(PAUSE_MINT);
 
         let quantity: u64 = metadata.copies.unwrap_or(1.into()).into();
 
         assert!(
-            self.token_metadata_by_id.insert(&metadata_id, &metadata).is_none(),
+            self.data_mut().token_metadata_by_id.insert(&metadata_id, &metadata).is_none(),
             "This ID already exists"
         );
 
         let miner_metadata: MinerMetadata = near_sdk::serde_json::from_str(&metadata.extra.unwrap()).expect("extra msg illegal!");
         assert!(
-            self.miner_metadata_by_id.insert(&metadata_id, &miner_metadata).is_none(),
+            self.data_mut().miner_metadata_by_id.insert(&metadata_id, &miner_metadata).is_none(),
             "This ID already exists"
         );
 
         for sn_number in 0..quantity {
-            let token = Token {
-                sn: format!("{}", sn_number),
-                owner_id: token_owner.as_ref().clone(),
-                metadata_id: metadata_id.clone(),
-                miner_metadata_id: metadata_id.clone(),
-
-                operator: token_owner.as_ref().clone(),
-                status: 0,
-                switch: 0,
-
-                power_left: 0,
-                power_deadline: 0,
-                approved_account_ids: Default::default(),
-            };
-            let token_id: String = format!("{}#{}", token.metadata_id, token.sn);
-            assert!(
-                self.miners_by_id.insert(&token_id, &token).is_none(),
-                "Miner already exists"
-            );
-            self.internal_add_token_to_owner(&token.owner_id, &token_id);
+            self.internal_mint_miner(token_owner.as_ref(), &metadata_id, sn_number);
         }
     }
 
@@ -92,19 +74,50 @@ impl Contract {
         metadata_id: TokenMetadataId, metadata: TokenMetadata
     ) {
 
-        self.assert_owner();
+        self.assert_role(Role::Minter);
+        self.assert_not_paused(PAUSE_MINT);
 
         let quantity: u64 = metadata.copies.unwrap_or(1.into()).into();
 
         assert!(
-            self.token_metadata_by_id.insert(&metadata_id, &metadata).is_none(),
+            self.data_mut().token_metadata_by_id.insert(&metadata_id, &metadata).is_none(),
             "This ID already exists"
         );
 
-        let mut power_map = self.powers_per_owner.get(power_owner.as_ref())
+        let mut power_map = self.data().powers_per_owner.get(power_owner.as_ref())
             .unwrap_or_else(|| UnorderedMap::new(unique_power_prefix(power_owner.as_ref())));
         power_map.insert(&metadata_id, &(quantity as u32));
-        self.powers_per_owner.insert(power_owner.as_ref(), &power_map);
+        self.data_mut().powers_per_owner.insert(power_owner.as_ref(), &power_map);
     }
 
 }
+
+impl Contract {
+
+    /// mint one miner of the type with the serial number, returns its token id
+    pub(crate) fn internal_mint_miner(&mut self, token_owner: &AccountId,
+        metadata_id: &TokenMetadataId, sn_number: u64
+    ) -> TokenId {
+        let token = Token {
+            sn: format!("{}", sn_number),
+            owner_id: token_owner.clone(),
+            metadata_id: metadata_id.clone(),
+            miner_metadata_id: metadata_id.clone(),
+
+            operator: token_owner.clone(),
+            status: 0,
+            switch: 0,
+
+            power_left: 0,
+            power_deadline: 0,
+            approved_account_ids: Default::default(),
+        };
+        let token_id: String = format!("{}#{}", token.metadata_id, token.sn);
+        assert!(
+            self.data_mut().miners_by_id.insert(&token_id, &token).is_none(),
+            "Miner already exists"
+        );
+        self.internal_add_token_to_owner(&token.owner_id, &token_id);
+        token_id
+    }
+}
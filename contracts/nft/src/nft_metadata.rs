@@ -46,6 +46,14 @@ pub struct MinerMetadata {
     pub w: u32,
 }
 
+/// custom metadata of Power Card, parsed from TokenMetadata::extra,
+/// power is added to power_left of the miner the card applied to.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PowerMetadata {
+    pub power: u32,
+}
+
 pub trait NonFungibleTokenMetadata {
     fn nft_metadata(&self) -> NFTMetadata;
 }
@@ -0,0 +1,70 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+
+    /// delegate operation of owner's miners to operator, such as a hosting service,
+    /// operator can power them on and off and apply power cards,
+    /// while ownership and award stay with owner.
+    /// Setting owner itself as operator takes the delegation back.
+    pub fn set_operator(&mut self, token_ids: Vec<TokenId>, operator: ValidAccountId) {
+        let owner_id = env::predecessor_account_id();
+        for token_id in token_ids.iter() {
+            let mut miner = self.data().miners_by_id.get(token_id).expect("Miner doesn't exist");
+            assert_eq!(miner.owner_id, owner_id, "Only owner can set operator of this miner");
+            self.assert_not_leased(token_id);
+            self.internal_set_operator(token_id, &mut miner, operator.as_ref());
+            self.data_mut().miners_by_id.insert(token_id, &miner);
+        }
+        env::log(
+            format!(
+                "@{} set @{} as operator of {} miners.", owner_id, operator, token_ids.len()
+            ).as_bytes());
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    /// miners delegated to the operator by their owners, leased miners not included
+    pub fn list_miners_by_operator(&self, operator: ValidAccountId,
+        from_index: u64, limit: u64
+    ) -> HashMap<TokenId, Token> {
+        let token_ids = match self.data().miners_per_operator.get(operator.as_ref()) {
+            Some(token_ids) => token_ids,
+            None => return HashMap::new(),
+        };
+        let keys = token_ids.as_vector();
+
+        (from_index..std::cmp::min(from_index + limit, keys.len())).map(
+            |index| (
+                keys.get(index).unwrap(),
+                self.data().miners_by_id.get(&keys.get(index).unwrap()).unwrap())
+        ).collect::<HashMap<_,_>>()
+    }
+}
+
+impl Contract {
+
+    /// change operator of a miner not leased and keep miners_per_operator in sync,
+    /// caller saves the miner.
+    pub(crate) fn internal_set_operator(&mut self, token_id: &TokenId, miner: &mut Token, operator: &AccountId) {
+        if miner.operator != miner.owner_id {
+            if let Some(mut token_ids) = self.data().miners_per_operator.get(&miner.operator) {
+                token_ids.remove(token_id);
+                if token_ids.is_empty() {
+                    self.data_mut().miners_per_operator.remove(&miner.operator);
+                } else {
+                    self.data_mut().miners_per_operator.insert(&miner.operator, &token_ids);
+                }
+            }
+        }
+        if operator != &miner.owner_id {
+            let mut token_ids = self.data().miners_per_operator.get(operator)
+                .unwrap_or_else(|| UnorderedSet::new(unique_operator_prefix(operator)));
+            token_ids.insert(token_id);
+            self.data_mut().miners_per_operator.insert(operator, &token_ids);
+        }
+        miner.operator = operator.clone();
+    }
+}
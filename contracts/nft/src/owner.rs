@@ -0,0 +1,45 @@
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+
+    /// first step of ownership transfer,
+    /// the proposed account must call accept_owner to become the owner.
+    /// Proposing again overrides the previous proposal.
+    pub fn propose_owner(&mut self, new_owner_id: ValidAccountId) {
+        self.assert_owner();
+        self.data_mut().proposed_owner_id = Some(new_owner_id.as_ref().clone());
+        env::log(
+            format!(
+                "Owner @{} proposed @{} as new owner.", self.data().owner_id, new_owner_id
+            ).as_bytes());
+    }
+
+    /// second step of ownership transfer, called by the proposed owner
+    pub fn accept_owner(&mut self) {
+        let caller = env::predecessor_account_id();
+        assert_eq!(
+            Some(&caller),
+            self.data().proposed_owner_id.as_ref(),
+            "Only proposed owner can accept ownership"
+        );
+        env::log(
+            format!(
+                "Ownership transferred from @{} to @{}.", self.data().owner_id, caller
+            ).as_bytes());
+        self.data_mut().owner_id = caller;
+        self.data_mut().proposed_owner_id = None;
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    pub fn get_owner(&self) -> AccountId {
+        self.data().owner_id.clone()
+    }
+
+    pub fn get_proposed_owner(&self) -> Option<AccountId> {
+        self.data().proposed_owner_id.clone()
+    }
+}
@@ -0,0 +1,53 @@
+use crate::*;
+
+/// bit flags of features that can be paused independently
+pub type PauseFlags = u8;
+/// nft_transfer, nft_transfer_call, batch_transfer_miners and batch_transfer_powers
+pub const PAUSE_TRANSFER: PauseFlags = 1;
+/// batch_poweron_miners and batch_poweroff_miners
+pub const PAUSE_POWER: PauseFlags = 2;
+/// settle_mining_epoch
+pub const PAUSE_SETTLEMENT: PauseFlags = 4;
+/// create_new_miners and issue_power_cards
+pub const PAUSE_MINT: PauseFlags = 8;
+
+#[near_bindgen]
+impl Contract {
+
+    /// pause features in flags, features already paused keep paused
+    pub fn pause(&mut self, flags: PauseFlags) {
+        self.assert_role(Role::Pauser);
+        self.data_mut().paused |= flags;
+        env::log(
+            format!(
+                "Paused {:#06b} by @{}, now {:#06b}.", flags, env::predecessor_account_id(), self.data().paused
+            ).as_bytes());
+    }
+
+    /// resume features in flags, other paused features keep paused
+    pub fn unpause(&mut self, flags: PauseFlags) {
+        self.assert_role(Role::Pauser);
+        self.data_mut().paused &= !flags;
+        env::log(
+            format!(
+                "Unpaused {:#06b} by @{}, now {:#06b}.", flags, env::predecessor_account_id(), self.data().paused
+            ).as_bytes());
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    pub fn get_paused(&self) -> PauseFlags {
+        self.data().paused
+    }
+}
+
+impl Contract {
+
+    pub(crate) fn assert_not_paused(&self, flag: PauseFlags) {
+        if self.data().paused & flag != 0 {
+            env::panic("This feature is paused.".as_bytes())
+        }
+    }
+}
@@ -0,0 +1,221 @@
+use crate::*;
+
+/// owner offers a miner for rent, paid in NEAR per epoch
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RentalOffer {
+    pub owner_id: AccountId,
+    pub price_per_epoch: U128,
+    /// longest lease renter can take
+    pub max_epochs: MiningEpoch,
+}
+
+/// while leased, renter is the operator of the miner and earns its award,
+/// owner can't transfer or power it.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Lease {
+    pub owner_id: AccountId,
+    pub renter_id: AccountId,
+    /// the lease expires in the settlement that enters this epoch
+    pub end_epoch: MiningEpoch,
+}
+
+#[near_bindgen]
+impl Contract {
+
+    /// offer a miner for rent, offering again updates price and max epochs.
+    /// Caller must attach deposit to cover storage of the offer, the rest is refunded.
+    #[payable]
+    pub fn offer_rental(&mut self, token_id: TokenId, price_per_epoch: U128, max_epochs: MiningEpoch) {
+        let initial_storage_usage = env::storage_usage();
+        let owner_id = env::predecessor_account_id();
+        let miner = self.data().miners_by_id.get(&token_id).expect("Miner doesn't exist");
+        assert_eq!(miner.owner_id, owner_id, "Only owner can offer this miner");
+        self.assert_not_leased(&token_id);
+        assert!(max_epochs > 0, "Max epochs should be a positive number");
+
+        self.data_mut().rental_offers.insert(&token_id, &RentalOffer {
+            owner_id: owner_id.clone(),
+            price_per_epoch,
+            max_epochs,
+        });
+        env::log(
+            format!(
+                "Offer {} for rent by @{} at {} yoctoⓃ per epoch, up to {} epochs.",
+                token_id, owner_id, price_per_epoch.0, max_epochs
+            ).as_bytes());
+        deposit_refund(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    /// storage deposit of the offer is returned to owner
+    pub fn cancel_rental_offer(&mut self, token_id: TokenId) {
+        let offer = self.data().rental_offers.get(&token_id).expect("Rental offer doesn't exist");
+        assert_eq!(offer.owner_id, env::predecessor_account_id(), "Only owner can cancel this offer");
+        self.internal_remove_rental_offer(&token_id);
+        env::log(format!("Cancel rental offer of {} by @{}.", token_id, offer.owner_id).as_bytes());
+    }
+
+    /// rent a miner for epochs, becoming its operator until the lease expires.
+    /// Caller must attach deposit to cover the rent and storage of the lease, the rest is refunded,
+    /// storage deposit of the lease is returned to it when the lease expires.
+    #[payable]
+    pub fn rent_miner(&mut self, token_id: TokenId, epochs: MiningEpoch) {
+        let renter_id = env::predecessor_account_id();
+        let offer = self.internal_remove_rental_offer(&token_id).expect("Rental offer doesn't exist");
+        let initial_storage_usage = env::storage_usage();
+        assert!(epochs > 0 && epochs <= offer.max_epochs, "Epochs should be in 1 to {}", offer.max_epochs);
+        let mut miner = self.data().miners_by_id.get(&token_id).expect("Miner doesn't exist");
+        assert_eq!(miner.owner_id, offer.owner_id, "Rental offer is stale");
+        assert_ne!(miner.owner_id, renter_id, "Owner can't rent its own miner");
+        assert_eq!(miner.switch, PW_OFF, "Miner should be powered off before rented");
+        assert_eq!(miner.operator, miner.owner_id, "Miner is delegated to an operator");
+
+        let end_epoch = self.data().current_mining_epoch + epochs;
+        miner.operator = renter_id.clone();
+        self.data_mut().miners_by_id.insert(&token_id, &miner);
+        self.data_mut().leases.insert(&token_id, &Lease {
+            owner_id: offer.owner_id.clone(),
+            renter_id: renter_id.clone(),
+            end_epoch,
+        });
+        let mut expiring = self.data().lease_expiries.get(&end_epoch)
+            .unwrap_or_else(|| UnorderedSet::new(format!("v{}", end_epoch).as_bytes().to_vec()));
+        expiring.insert(&token_id);
+        self.data_mut().lease_expiries.insert(&end_epoch, &expiring);
+
+        let rent = offer.price_per_epoch.0 * Balance::from(epochs);
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        let required_cost = rent + STORAGE_PRICE_PER_BYTE * Balance::from(storage_used);
+        let attached_deposit = env::attached_deposit();
+        assert!(
+            required_cost <= attached_deposit,
+            "Must attach {} yoctoⓃ to cover rent and storage", required_cost
+        );
+        Promise::new(offer.owner_id.clone()).transfer(rent);
+        if attached_deposit > required_cost {
+            Promise::new(renter_id.clone()).transfer(attached_deposit - required_cost);
+        }
+        env::log(
+            format!(
+                "@{} rents {} from @{} for {} yoctoⓃ until epoch {}.",
+                renter_id, token_id, offer.owner_id, rent, end_epoch
+            ).as_bytes());
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    pub fn get_rental_offer(&self, token_id: TokenId) -> Option<RentalOffer> {
+        self.data().rental_offers.get(&token_id)
+    }
+
+    pub fn list_rental_offers(&self, from_index: u64, limit: u64) -> HashMap<TokenId, RentalOffer> {
+        let keys = self.data().rental_offers.keys_as_vector();
+
+        (from_index..std::cmp::min(from_index + limit, keys.len())).map(
+            |index| (
+                keys.get(index).unwrap(),
+                self.data().rental_offers.get(&keys.get(index).unwrap()).unwrap())
+        ).collect::<HashMap<_,_>>()
+    }
+
+    pub fn get_lease(&self, token_id: TokenId) -> Option<Lease> {
+        self.data().leases.get(&token_id)
+    }
+
+    pub fn list_leases(&self, from_index: u64, limit: u64) -> HashMap<TokenId, Lease> {
+        let keys = self.data().leases.keys_as_vector();
+
+        (from_index..std::cmp::min(from_index + limit, keys.len())).map(
+            |index| (
+                keys.get(index).unwrap(),
+                self.data().leases.get(&keys.get(index).unwrap()).unwrap())
+        ).collect::<HashMap<_,_>>()
+    }
+}
+
+impl Contract {
+
+    /// storage deposit of the offer is returned to owner
+    fn internal_remove_rental_offer(&mut self, token_id: &TokenId) -> Option<RentalOffer> {
+        let initial_storage_usage = env::storage_usage();
+        let offer = self.data_mut().rental_offers.remove(token_id)?;
+        refund_released_storage(
+            offer.owner_id.clone(), initial_storage_usage.saturating_sub(env::storage_usage()),
+        );
+        Some(offer)
+    }
+
+    pub(crate) fn assert_not_leased(&self, token_id: &TokenId) {
+        if self.data().leases.get(token_id).is_some() {
+            env::panic("Miner is leased.".as_bytes())
+        }
+    }
+
+    /// account whose mining entity gets the thash of this miner,
+    /// renter while leased, otherwise owner.
+    pub(crate) fn get_miner_beneficiary(&self, token_id: &TokenId, miner: &Token) -> AccountId {
+        self.data().leases.get(token_id)
+            .map(|lease| lease.renter_id)
+            .unwrap_or_else(|| miner.owner_id.clone())
+    }
+
+    /// called in mining settlement after entering new epoch,
+    /// to power off miners whose lease expires and return them to owner,
+    /// storage deposit of each lease is returned to its renter.
+    pub(crate) fn settle_leases(&mut self) {
+        let current_mining_epoch = self.data().current_mining_epoch;
+        let mut expiring = match self.data_mut().lease_expiries.remove(&current_mining_epoch) {
+            Some(expiring) => expiring,
+            None => return,
+        };
+        for token_id in expiring.to_vec() {
+            let mut miner = self.data().miners_by_id.get(&token_id).expect("Internal Error: Miner not exist.");
+            if miner.status == ST_NORMAL && miner.switch == PW_ON {
+                self.internal_power_off(&token_id, &mut miner);
+            }
+            let initial_storage_usage = env::storage_usage();
+            let lease = self.data_mut().leases.remove(&token_id).expect("Internal Error: no lease");
+            expiring.remove(&token_id);
+            refund_released_storage(
+                lease.renter_id.clone(), initial_storage_usage.saturating_sub(env::storage_usage()),
+            );
+            miner.operator = lease.owner_id;
+            self.data_mut().miners_by_id.insert(&token_id, &miner);
+            env::log(
+                format!(
+                    "Lease of {} by @{} expired in epoch {}.", token_id, lease.renter_id, current_mining_epoch
+                ).as_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn test_lease_expires() {
+        let mut contract = setup_contract(accounts(2), 1);
+        let token_id = "mid-001#0".to_string();
+        call_with(accounts(2), ONE_NEAR);
+        contract.offer_rental(token_id.clone(), ONE_NEAR.into(), 10);
+
+        call_with(accounts(3), 3 * ONE_NEAR);
+        contract.rent_miner(token_id.clone(), 2);
+        assert!(contract.get_rental_offer(token_id.clone()).is_none());
+        assert_eq!(contract.get_lease(token_id.clone()).unwrap().end_epoch, 2);
+        assert_eq!(contract.nft_token(token_id.clone()).unwrap().operator, accounts(3).to_string());
+
+        contract.data_mut().current_mining_epoch = 2;
+        contract.settle_leases();
+        assert!(contract.get_lease(token_id.clone()).is_none());
+        let miner = contract.nft_token(token_id).unwrap();
+        assert_eq!(miner.operator, accounts(2).to_string());
+        assert_eq!(miners_of(&contract, accounts(2)), vec!["mid-001#0".to_string()]);
+    }
+}
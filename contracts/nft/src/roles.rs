@@ -0,0 +1,90 @@
+use crate::*;
+
+/// privileged roles of this contract,
+/// contract owner implicitly holds all of them.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// grant and revoke other roles, change mining params
+    Admin,
+    /// create miners and issue power cards
+    Minter,
+    /// settle mining epochs
+    Settler,
+    /// pause and unpause contract features
+    Pauser,
+}
+
+#[near_bindgen]
+impl Contract {
+
+    /// only owner can grant admin, admins can grant other roles
+    pub fn grant_role(&mut self, role: Role, account_id: ValidAccountId) {
+        self.assert_role_manager(role);
+        let mut members = self.data().roles.get(&role)
+            .unwrap_or_else(|| UnorderedSet::new(unique_role_prefix(role)));
+        if members.insert(account_id.as_ref()) {
+            self.data_mut().roles.insert(&role, &members);
+            env::log(
+                format!(
+                    "Grant {:?} to @{} by @{}.", role, account_id, env::predecessor_account_id()
+                ).as_bytes());
+        }
+    }
+
+    /// only owner can revoke admin, admins can revoke other roles
+    pub fn revoke_role(&mut self, role: Role, account_id: ValidAccountId) {
+        self.assert_role_manager(role);
+        if let Some(mut members) = self.data().roles.get(&role) {
+            if members.remove(account_id.as_ref()) {
+                if members.is_empty() {
+                    self.data_mut().roles.remove(&role);
+                } else {
+                    self.data_mut().roles.insert(&role, &members);
+                }
+                env::log(
+                    format!(
+                        "Revoke {:?} from @{} by @{}.", role, account_id, env::predecessor_account_id()
+                    ).as_bytes());
+            }
+        }
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    /// accounts granted with the role, owner not included
+    pub fn get_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.data().roles.get(&role).map(|members| members.to_vec()).unwrap_or_default()
+    }
+
+    pub fn has_role(&self, role: Role, account_id: ValidAccountId) -> bool {
+        self.internal_has_role(role, account_id.as_ref())
+    }
+}
+
+impl Contract {
+
+    pub(crate) fn internal_has_role(&self, role: Role, account_id: &AccountId) -> bool {
+        account_id == &self.data().owner_id
+            || self.data().roles.get(&role).map(|members| members.contains(account_id)).unwrap_or(false)
+    }
+
+    pub(crate) fn assert_role(&self, role: Role) {
+        assert!(
+            self.internal_has_role(role, &env::predecessor_account_id()),
+            "{:?} role required",
+            role
+        );
+    }
+
+    fn assert_role_manager(&self, role: Role) {
+        if role == Role::Admin {
+            self.assert_owner();
+        } else {
+            self.assert_role(Role::Admin);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::*;
+
+/// primary sale of a miner type, each purchase mints new miners
+/// with serial numbers following the existing copies of that type.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleCampaign {
+    /// price of each miner in yoctoNEAR
+    pub price: U128,
+    /// block timestamp in nanoseconds the sale starts at
+    pub start_at: U64,
+    /// max miners of this type one account can buy, counted across all campaigns of the type
+    pub limit_per_account: u32,
+    /// miners left for sale
+    pub supply_left: u32,
+    /// account receiving the proceeds
+    pub treasury_id: AccountId,
+}
+
+#[near_bindgen]
+impl Contract {
+
+    /// start selling new miners of an existing miner type
+    pub fn create_sale_campaign(&mut self, metadata_id: MinerMetadataId,
+        price: U128, start_at: U64, limit_per_account: u32, supply: u32, treasury_id: ValidAccountId
+    ) {
+        self.assert_role(Role::Minter);
+        assert!(
+            self.data().miner_metadata_by_id.get(&metadata_id).is_some(),
+            "Miner type doesn't exist"
+        );
+        assert!(self.data().sale_campaigns.get(&metadata_id).is_none(), "Campaign already exists");
+
+        self.data_mut().sale_campaigns.insert(&metadata_id, &SaleCampaign {
+            price,
+            start_at,
+            limit_per_account,
+            supply_left: supply,
+            treasury_id: treasury_id.as_ref().clone(),
+        });
+        env::log(
+            format!(
+                "Sale of {} created, {} miners at {} yoctoⓃ from {}, proceeds to @{}.",
+                metadata_id, supply, price.0, start_at.0, treasury_id
+            ).as_bytes());
+    }
+
+    /// stop selling, miners already sold are not affected
+    pub fn close_sale_campaign(&mut self, metadata_id: MinerMetadataId) {
+        self.assert_role(Role::Minter);
+        self.data_mut().sale_campaigns.remove(&metadata_id).expect("Campaign doesn't exist");
+        env::log(format!("Sale of {} closed.", metadata_id).as_bytes());
+    }
+
+    /// buy new miners in a sale campaign.
+    /// Caller must attach deposit to cover the price and storage of new miners, the rest is refunded.
+    #[payable]
+    pub fn buy_miners(&mut self, metadata_id: MinerMetadataId, count: u32) -> Vec<TokenId> {
+        self.assert_not_paused(PAUSE_MINT);
+        let initial_storage_usage = env::storage_usage();
+        let buyer_id = env::predecessor_account_id();
+        let mut campaign = self.data().sale_campaigns.get(&metadata_id).expect("Campaign doesn't exist");
+        assert!(count > 0, "Count should be a positive number");
+        assert!(env::block_timestamp() >= campaign.start_at.0, "Sale not started yet");
+        assert!(count <= campaign.supply_left, "Only {} miners left for sale", campaign.supply_left);
+        let purchase_key = (metadata_id.clone(), buyer_id.clone());
+        let bought = self.data().sale_purchases.get(&purchase_key).unwrap_or(0);
+        assert!(
+            bought + count <= campaign.limit_per_account,
+            "Exceeds limit per account, only {} miners left to buy", campaign.limit_per_account - bought
+        );
+
+        campaign.supply_left -= count;
+        self.data_mut().sale_campaigns.insert(&metadata_id, &campaign);
+        self.data_mut().sale_purchases.insert(&purchase_key, &(bought + count));
+
+        let mut metadata = self.data().token_metadata_by_id.get(&metadata_id)
+            .expect("Internal Error: no metadata of this miner type");
+        let next_sn: u64 = metadata.copies.unwrap_or_else(|| 1.into()).into();
+        let token_ids: Vec<TokenId> = (next_sn..next_sn + count as u64)
+            .map(|sn_number| self.internal_mint_miner(&buyer_id, &metadata_id, sn_number))
+            .collect();
+        metadata.copies = Some((next_sn + count as u64).into());
+        self.data_mut().token_metadata_by_id.insert(&metadata_id, &metadata);
+
+        let total_price = campaign.price.0 * Balance::from(count);
+        let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+        let required_cost = total_price + STORAGE_PRICE_PER_BYTE * Balance::from(storage_used);
+        let attached_deposit = env::attached_deposit();
+        assert!(
+            required_cost <= attached_deposit,
+            "Must attach {} yoctoⓃ to cover price and storage", required_cost
+        );
+        Promise::new(campaign.treasury_id).transfer(total_price);
+        if attached_deposit > required_cost {
+            Promise::new(buyer_id.clone()).transfer(attached_deposit - required_cost);
+        }
+
+        env::log(
+            format!(
+                "Sell {} new miners of {} to @{} for {} yoctoⓃ.", count, metadata_id, buyer_id, total_price
+            ).as_bytes());
+        token_ids
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    pub fn get_sale_campaign(&self, metadata_id: MinerMetadataId) -> Option<SaleCampaign> {
+        self.data().sale_campaigns.get(&metadata_id)
+    }
+
+    pub fn list_sale_campaigns(&self, from_index: u64, limit: u64
+    ) -> HashMap<MinerMetadataId, SaleCampaign> {
+        let keys = self.data().sale_campaigns.keys_as_vector();
+
+        (from_index..std::cmp::min(from_index + limit, keys.len())).map(
+            |index| (
+                keys.get(index).unwrap(),
+                self.data().sale_campaigns.get(&keys.get(index).unwrap()).unwrap())
+        ).collect::<HashMap<_,_>>()
+    }
+
+    /// miners of the type the account bought in sale campaigns
+    pub fn get_sale_purchased(&self, metadata_id: MinerMetadataId, account_id: ValidAccountId) -> u32 {
+        self.data().sale_purchases.get(&(metadata_id, account_id.as_ref().clone())).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::*;
+    use near_sdk::test_utils::accounts;
+
+    #[test]
+    fn test_buy_many_miners() {
+        let mut contract = setup_contract(accounts(1), 1);
+        contract.create_sale_campaign("mid-001".to_string(), ONE_NEAR.into(), 0.into(), 5, 10, accounts(1));
+
+        call_with(accounts(2), 10 * ONE_NEAR);
+        let token_ids = contract.buy_miners("mid-001".to_string(), 3);
+        assert_eq!(token_ids, vec!["mid-001#1", "mid-001#2", "mid-001#3"]);
+        assert_eq!(miners_of(&contract, accounts(2)), token_ids);
+        assert_eq!(contract.get_sale_purchased("mid-001".to_string(), accounts(2)), 3);
+
+        // bought miners can be moved on like any other
+        contract.batch_transfer_miners(accounts(3), vec![token_ids[0].clone()]);
+        assert_eq!(miners_of(&contract, accounts(2)), token_ids[1..].to_vec());
+        assert_eq!(miners_of(&contract, accounts(3)), vec![token_ids[0].clone()]);
+    }
+}
@@ -0,0 +1,52 @@
+//! helpers shared by unit tests of this contract
+
+use crate::*;
+use near_sdk::serde_json::json;
+use near_sdk::test_utils::{accounts, VMContextBuilder};
+use near_sdk::{testing_env, MockedBlockchain};
+
+pub(crate) const ONE_NEAR: Balance = 1_000_000_000_000_000_000_000_000;
+
+pub(crate) fn get_context(predecessor: ValidAccountId) -> VMContextBuilder {
+    let mut builder = VMContextBuilder::new();
+    builder
+        .current_account_id(accounts(0))
+        .predecessor_account_id(predecessor);
+    builder
+}
+
+/// switch to the caller, attaching deposit
+pub(crate) fn call_with(predecessor: ValidAccountId, deposit: Balance) {
+    testing_env!(get_context(predecessor).attached_deposit(deposit).build());
+}
+
+pub(crate) fn miner_metadata(copies: u64) -> TokenMetadata {
+    near_sdk::serde_json::from_value(json!({
+        "copies": copies.to_string(),
+        "extra": json!({"producer": "bitmain", "category": "s19", "thash": 100, "w": 10}).to_string(),
+    })).unwrap()
+}
+
+pub(crate) fn power_metadata(copies: u64, power: u32) -> TokenMetadata {
+    near_sdk::serde_json::from_value(json!({
+        "copies": copies.to_string(),
+        "extra": json!({"power": power}).to_string(),
+    })).unwrap()
+}
+
+/// contract owned by accounts(1), with `copies` miners of "mid-001" minted to owner
+pub(crate) fn setup_contract(owner: ValidAccountId, copies: u64) -> Contract {
+    call_with(accounts(1), 0);
+    let mut contract = Contract::new(accounts(1), None, None, None);
+    contract.create_new_miners(owner, "mid-001".to_string(), miner_metadata(copies));
+    contract
+}
+
+/// token ids of the account in miners_per_owner
+pub(crate) fn miners_of(contract: &Contract, account_id: ValidAccountId) -> Vec<TokenId> {
+    let mut token_ids: Vec<TokenId> = contract.list_miners_by_onwer(account_id, 0, 100)
+        .into_keys()
+        .collect();
+    token_ids.sort();
+    token_ids
+}
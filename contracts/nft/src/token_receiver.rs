@@ -0,0 +1,183 @@
+use crate::*;
+use near_sdk::{ext_contract, Gas, PromiseOrValue, PromiseResult};
+
+const GAS_FOR_FT_TRANSFER: Gas = 10_000_000_000_000;
+const GAS_FOR_RESOLVE_PAYOUT: Gas = 5_000_000_000_000;
+
+/// what vBTC sent by ft_transfer_call is used for, parsed from msg,
+/// such as `{"buy_miner": {"token_id": "mid-001#0"}}` or `{"buy_power": {"card": "power-001", "count": 10}}`
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum TokenReceiverMessage {
+    BuyMiner { token_id: TokenId },
+    BuyPower { card: TokenMetadataId, count: u32 },
+    BidMiner { token_id: TokenId },
+}
+
+#[ext_contract(ext_fungible_token)]
+pub trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+#[ext_contract(ext_payout)]
+pub trait VbtcPayoutResolver {
+    fn on_vbtc_payout(&mut self, receiver_id: AccountId, amount: U128);
+}
+
+#[near_bindgen]
+impl Contract {
+
+    /// set the vBTC contract whose tokens are accepted by ft_on_transfer
+    pub fn set_vbtc_token_id(&mut self, token_id: ValidAccountId) {
+        self.assert_owner();
+        self.data_mut().vbtc_token_id = Some(token_id.as_ref().clone());
+        env::log(format!("Set vBTC token to @{}.", token_id).as_bytes());
+    }
+
+    /// set vBTC price of each power card of the type, None stops selling it
+    pub fn set_power_card_price(&mut self, card: TokenMetadataId, price: Option<U128>) {
+        self.assert_role(Role::Minter);
+        assert!(
+            self.data().token_metadata_by_id.get(&card).is_some()
+                && self.data().miner_metadata_by_id.get(&card).is_none(),
+            "Power card doesn't exist"
+        );
+        match price {
+            Some(price) => {
+                assert!(price.0 > 0, "Price should be a positive number");
+                // cards for sale must have power, so settlement of auto-compounding never fails on it
+                self.get_card_power(&card);
+                self.data_mut().power_card_prices.insert(&card, &price.into());
+                env::log(format!("Sell power card {} at {} vBTC.", card, price.0).as_bytes());
+            }
+            None => {
+                self.data_mut().power_card_prices.remove(&card);
+                env::log(format!("Stop selling power card {}.", card).as_bytes());
+            }
+        }
+    }
+
+    /// send vBTC received from power card sales out of this contract,
+    /// escrowed bids can't be withdrawn
+    pub fn withdraw_vbtc(&mut self, receiver_id: ValidAccountId, amount: U128) -> Promise {
+        self.assert_owner();
+        self.assert_vbtc_token_set();
+        assert!(amount.0 <= self.data().vbtc_revenue, "Not enough vBTC revenue");
+        self.data_mut().vbtc_revenue -= amount.0;
+        env::log(format!("Withdraw {} vBTC to @{}.", amount.0, receiver_id).as_bytes());
+        self.internal_send_vbtc(receiver_id.as_ref(), amount.into())
+    }
+
+    /// resend vBTC of caller's failed payouts,
+    /// caller should have registered in vBTC contract.
+    pub fn claim_vbtc_payout(&mut self) -> Promise {
+        self.assert_vbtc_token_set();
+        let account_id = env::predecessor_account_id();
+        let amount = self.data_mut().failed_vbtc_payouts.remove(&account_id).expect("No failed payout");
+        env::log(format!("@{} claims {} vBTC of failed payouts.", account_id, amount).as_bytes());
+        self.internal_send_vbtc(&account_id, amount)
+    }
+
+    /// callback of internal_send_vbtc, records the payout if the transfer failed
+    pub fn on_vbtc_payout(&mut self, receiver_id: AccountId, amount: U128) {
+        assert_self();
+        if let PromiseResult::Successful(_) = env::promise_result(0) {
+            return;
+        }
+        let failed = self.data().failed_vbtc_payouts.get(&receiver_id).unwrap_or(0) + amount.0;
+        self.data_mut().failed_vbtc_payouts.insert(&receiver_id, &failed);
+        env::log(format!("Payout of {} vBTC to @{} failed, left to claim.", amount.0, receiver_id).as_bytes());
+    }
+
+    pub fn get_failed_vbtc_payout(&self, account_id: ValidAccountId) -> U128 {
+        self.data().failed_vbtc_payouts.get(account_id.as_ref()).unwrap_or(0).into()
+    }
+
+    pub fn get_vbtc_token_id(&self) -> Option<AccountId> {
+        self.data().vbtc_token_id.clone()
+    }
+
+    pub fn get_vbtc_revenue(&self) -> U128 {
+        self.data().vbtc_revenue.into()
+    }
+
+    pub fn get_power_card_prices(&self) -> HashMap<TokenMetadataId, U128> {
+        self.data().power_card_prices.iter().map(|(card, price)| (card, price.into())).collect()
+    }
+
+    /// NEP-141 receiver, only vBTC is accepted.
+    /// Returns amount unused, which would be refunded by vBTC contract.
+    pub fn ft_on_transfer(
+        &mut self,
+        sender_id: ValidAccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        self.assert_vbtc_token_set();
+        assert_eq!(
+            Some(env::predecessor_account_id()),
+            self.data().vbtc_token_id,
+            "Only vBTC is accepted"
+        );
+        let message = near_sdk::serde_json::from_str::<TokenReceiverMessage>(&msg).expect("Illegal msg");
+        let amount: Balance = amount.into();
+        let used = match message {
+            TokenReceiverMessage::BuyMiner { token_id } => {
+                let listing = self.data().listings.get(&token_id).expect("Listing doesn't exist");
+                assert_eq!(listing.currency, Currency::Vbtc, "This miner is not sold in vBTC");
+                let price: Balance = listing.price.into();
+                assert!(amount >= price, "Must send {} vBTC to buy this miner", price);
+                self.internal_buy_miner(&token_id, sender_id.as_ref());
+                self.internal_send_vbtc(&listing.owner_id, price);
+                price
+            }
+            TokenReceiverMessage::BuyPower { card, count } => {
+                let price = self.data().power_card_prices.get(&card).expect("Power card is not for sale");
+                assert!(count > 0, "Count should be a positive number");
+                let cost = price * Balance::from(count);
+                assert!(amount >= cost, "Must send {} vBTC to buy these power cards", cost);
+                self.internal_add_powers(sender_id.as_ref(), &card, count);
+                self.data_mut().vbtc_revenue += cost;
+                env::log(
+                    format!(
+                        "Sell {} power card {} to @{} for {} vBTC.", count, card, sender_id, cost
+                    ).as_bytes());
+                cost
+            }
+            TokenReceiverMessage::BidMiner { token_id } => {
+                let auction = self.data().auctions.get(&token_id).expect("Auction doesn't exist");
+                assert_eq!(auction.currency, Currency::Vbtc, "This auction is not in vBTC");
+                self.internal_bid(&token_id, sender_id.as_ref(), amount);
+                amount
+            }
+        };
+        PromiseOrValue::Value((amount - used).into())
+    }
+}
+
+impl Contract {
+
+    pub(crate) fn assert_vbtc_token_set(&self) {
+        assert!(self.data().vbtc_token_id.is_some(), "vBTC token is not set");
+    }
+
+    /// pay vBTC held by this contract, if receiver is not registered in vBTC contract,
+    /// the payout is recorded in on_vbtc_payout for it to claim later.
+    pub(crate) fn internal_send_vbtc(&self, receiver_id: &AccountId, amount: Balance) -> Promise {
+        ext_fungible_token::ft_transfer(
+            receiver_id.clone(),
+            amount.into(),
+            None,
+            self.data().vbtc_token_id.as_ref().unwrap(),
+            1,
+            GAS_FOR_FT_TRANSFER,
+        ).then(ext_payout::on_vbtc_payout(
+            receiver_id.clone(),
+            amount.into(),
+            &env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_PAYOUT,
+        ))
+    }
+}
@@ -0,0 +1,270 @@
+use crate::*;
+use near_sdk::Gas;
+
+/// gas kept for upgrade itself, the rest is given to migrate
+const GAS_FOR_UPGRADE_CALL: Gas = 50_000_000_000_000;
+
+/// Contract layout deployed before state versioning,
+/// the whole struct was stored as raw borsh, without VersionedContractData.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV1 {
+    pub owner_id: AccountId,
+    pub token_metadata_by_id: UnorderedMap<TokenMetadataId, TokenMetadata>,
+    pub miner_metadata_by_id: UnorderedMap<MinerMetadataId, MinerMetadata>,
+    pub miners_by_id: UnorderedMap<TokenId, Token>,
+    pub miners_per_owner: LookupMap<AccountId, UnorderedMap<MinerMetadataId, UnorderedSet<TokenId>>>,
+    pub powers_per_owner: LookupMap<AccountId, UnorderedMap<TokenMetadataId, u32>>,
+    pub current_mining_epoch: MiningEpoch,
+    pub epoch_award: Balance,
+    pub min_interval_of_epoch: BlockHeight,
+    pub current_epoch_start_at: BlockHeight,
+    pub current_total_thash: Thash,
+    pub mining_entities: UnorderedMap<AccountId, Thash>,
+    pub power_events: LookupMap<MiningEpoch, UnorderedSet<TokenId>>,
+    pub mining_pools: UnorderedMap<AccountId, MiningPool>,
+}
+
+/// Contract data layout of the first versioned state,
+/// with roles, pause, emission schedule and queued param changes.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractDataV2 {
+    pub owner_id: AccountId,
+    pub proposed_owner_id: Option<AccountId>,
+    pub roles: LookupMap<Role, UnorderedSet<AccountId>>,
+    pub paused: PauseFlags,
+    pub token_metadata_by_id: UnorderedMap<TokenMetadataId, TokenMetadata>,
+    pub miner_metadata_by_id: UnorderedMap<MinerMetadataId, MinerMetadata>,
+    pub miners_by_id: UnorderedMap<TokenId, Token>,
+    pub miners_per_owner: LookupMap<AccountId, UnorderedMap<MinerMetadataId, UnorderedSet<TokenId>>>,
+    pub powers_per_owner: LookupMap<AccountId, UnorderedMap<TokenMetadataId, u32>>,
+    pub current_mining_epoch: MiningEpoch,
+    pub epoch_award: Balance,
+    pub halving_interval: MiningEpoch,
+    pub emission_cap: Option<Balance>,
+    pub total_emission: Balance,
+    pub min_interval_of_epoch: BlockHeight,
+    pub current_epoch_start_at: BlockHeight,
+    pub current_total_thash: Thash,
+    pub mining_entities: UnorderedMap<AccountId, Thash>,
+    pub power_events: LookupMap<MiningEpoch, UnorderedSet<TokenId>>,
+    pub mining_pools: UnorderedMap<AccountId, MiningPool>,
+    pub param_timelock: MiningEpoch,
+    pub pending_param_changes: UnorderedMap<u64, ParamChange>,
+    pub next_param_change_id: u64,
+}
+
+/// fields added since v1 get the same values as in new,
+/// v1 paid the same award in every epoch, so it is what has been emitted.
+impl From<ContractV1> for ContractDataV2 {
+    fn from(v1: ContractV1) -> Self {
+        ContractDataV2 {
+            owner_id: v1.owner_id,
+            proposed_owner_id: None,
+            roles: LookupMap::new(b"j".to_vec()),
+            paused: 0,
+            token_metadata_by_id: v1.token_metadata_by_id,
+            miner_metadata_by_id: v1.miner_metadata_by_id,
+            miners_by_id: v1.miners_by_id,
+            miners_per_owner: v1.miners_per_owner,
+            powers_per_owner: v1.powers_per_owner,
+            current_mining_epoch: v1.current_mining_epoch,
+            epoch_award: v1.epoch_award,
+            halving_interval: DEFAULT_HALVING_INTERVAL,
+            emission_cap: None,
+            total_emission: Balance::from(v1.current_mining_epoch) * v1.epoch_award,
+            min_interval_of_epoch: v1.min_interval_of_epoch,
+            current_epoch_start_at: v1.current_epoch_start_at,
+            current_total_thash: v1.current_total_thash,
+            mining_entities: v1.mining_entities,
+            power_events: v1.power_events,
+            mining_pools: v1.mining_pools,
+            param_timelock: DEFAULT_PARAM_TIMELOCK,
+            pending_param_changes: UnorderedMap::new(b"i".to_vec()),
+            next_param_change_id: 0,
+        }
+    }
+}
+
+/// market, auction, rental and operator fields added since v2 start empty
+impl From<ContractDataV2> for ContractData {
+    fn from(v2: ContractDataV2) -> Self {
+        ContractData {
+            owner_id: v2.owner_id,
+            proposed_owner_id: v2.proposed_owner_id,
+            roles: v2.roles,
+            paused: v2.paused,
+            token_metadata_by_id: v2.token_metadata_by_id,
+            miner_metadata_by_id: v2.miner_metadata_by_id,
+            miners_by_id: v2.miners_by_id,
+            miners_per_owner: v2.miners_per_owner,
+            powers_per_owner: v2.powers_per_owner,
+            current_mining_epoch: v2.current_mining_epoch,
+            epoch_award: v2.epoch_award,
+            halving_interval: v2.halving_interval,
+            award_base_epoch: 0,
+            emission_cap: v2.emission_cap,
+            total_emission: v2.total_emission,
+            min_interval_of_epoch: v2.min_interval_of_epoch,
+            current_epoch_start_at: v2.current_epoch_start_at,
+            current_total_thash: v2.current_total_thash,
+            mining_entities: v2.mining_entities,
+            power_events: v2.power_events,
+            mining_pools: v2.mining_pools,
+            param_timelock: v2.param_timelock,
+            pending_param_changes: v2.pending_param_changes,
+            next_param_change_id: v2.next_param_change_id,
+            vbtc_token_id: None,
+            listings: UnorderedMap::new(b"k".to_vec()),
+            sale_campaigns: UnorderedMap::new(b"l".to_vec()),
+            sale_purchases: LookupMap::new(b"m".to_vec()),
+            power_card_prices: UnorderedMap::new(b"n".to_vec()),
+            vbtc_revenue: 0,
+            failed_vbtc_payouts: LookupMap::new(b"A".to_vec()),
+            auto_compounds: LookupMap::new(b"o".to_vec()),
+            auctions: UnorderedMap::new(b"p".to_vec()),
+            rental_offers: UnorderedMap::new(b"q".to_vec()),
+            leases: UnorderedMap::new(b"s".to_vec()),
+            lease_expiries: LookupMap::new(b"t".to_vec()),
+            miners_per_operator: LookupMap::new(b"u".to_vec()),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+
+    /// Convert state written by previous code into current layout,
+    /// should be called by this contract itself right after new code deployed.
+    /// Raw ContractV1 state starts with the length of owner_id, which is at least 2,
+    /// so it would never be taken as a tag of VersionedContractData.
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        assert_self();
+        let state = env::storage_read(b"STATE").expect("Not initialized");
+        if let Ok(contract) = Contract::try_from_slice(&state) {
+            return match contract.data {
+                VersionedContractData::V2(v2) => {
+                    env::log(b"Migrate state from v2.");
+                    Self { data: VersionedContractData::Current(v2.into()) }
+                }
+                _ => contract,
+            };
+        }
+        let v1 = ContractV1::try_from_slice(&state).expect("Unknown state layout");
+        env::log(b"Migrate state from v1.");
+        Self { data: VersionedContractData::Current(ContractDataV2::from(v1).into()) }
+    }
+
+    /// Deploy new code to this contract, then call migrate on it in the same batch,
+    /// so the code and the state are upgraded together or not at all.
+    /// Code is passed as borsh raw bytes rather than json.
+    pub fn upgrade(&self, #[serializer(borsh)] code: Vec<u8>) -> Promise {
+        self.assert_owner();
+        env::log(format!("Upgrade to code with {} bytes.", code.len()).as_bytes());
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(
+                b"migrate".to_vec(),
+                vec![],
+                0,
+                env::prepaid_gas() - env::used_gas() - GAS_FOR_UPGRADE_CALL,
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, MockedBlockchain};
+
+    fn get_context(predecessor: ValidAccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .predecessor_account_id(predecessor);
+        builder
+    }
+
+    fn v1_fixture() -> ContractV1 {
+        ContractV1 {
+            owner_id: accounts(1).into(),
+            token_metadata_by_id: UnorderedMap::new(b"d".to_vec()),
+            miner_metadata_by_id: UnorderedMap::new(b"e".to_vec()),
+            miners_by_id: UnorderedMap::new(b"c".to_vec()),
+            miners_per_owner: LookupMap::new(b"a".to_vec()),
+            powers_per_owner: LookupMap::new(b"b".to_vec()),
+            current_mining_epoch: 7,
+            epoch_award: DEFAULT_EPOCH_AWARD,
+            min_interval_of_epoch: 3600,
+            current_epoch_start_at: 100,
+            current_total_thash: 0,
+            mining_entities: UnorderedMap::new(b"g".to_vec()),
+            power_events: LookupMap::new(b"h".to_vec()),
+            mining_pools: UnorderedMap::new(b"f".to_vec()),
+        }
+    }
+
+    #[test]
+    fn test_migrate_from_v1() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut v1 = v1_fixture();
+        let mut powers = UnorderedMap::new(unique_power_prefix(accounts(2).as_ref()));
+        powers.insert(&"power-001".to_string(), &10);
+        v1.powers_per_owner.insert(accounts(2).as_ref(), &powers);
+        env::state_write(&v1);
+
+        let contract = Contract::migrate();
+        let data = contract.data();
+        assert_eq!(data.owner_id, accounts(1).to_string());
+        assert_eq!(data.paused, 0);
+        assert_eq!(data.current_mining_epoch, 7);
+        assert_eq!(data.halving_interval, DEFAULT_HALVING_INTERVAL);
+        assert_eq!(data.total_emission, 7 * DEFAULT_EPOCH_AWARD);
+        assert_eq!(data.emission_cap, None);
+        let powers = data.powers_per_owner.get(accounts(2).as_ref()).unwrap();
+        assert_eq!(powers.get(&"power-001".to_string()), Some(10));
+    }
+
+    #[test]
+    fn test_migrate_from_v2() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut v2 = ContractDataV2::from(v1_fixture());
+        v2.proposed_owner_id = Some(accounts(2).into());
+        v2.paused = PAUSE_SETTLEMENT;
+        v2.halving_interval = 100;
+        v2.next_param_change_id = 3;
+        env::state_write(&Contract { data: VersionedContractData::V2(v2) });
+
+        let contract = Contract::migrate();
+        let data = contract.data();
+        assert_eq!(data.owner_id, accounts(1).to_string());
+        assert_eq!(data.proposed_owner_id, Some(accounts(2).into()));
+        assert_eq!(data.paused, PAUSE_SETTLEMENT);
+        assert_eq!(data.halving_interval, 100);
+        assert_eq!(data.next_param_change_id, 3);
+        assert_eq!(data.vbtc_token_id, None);
+        assert_eq!(data.listings.len(), 0);
+    }
+
+    #[test]
+    fn test_migrate_current_state() {
+        testing_env!(get_context(accounts(0)).build());
+        let contract = Contract::new(accounts(1), None, None, None);
+        env::state_write(&contract);
+
+        let contract = Contract::migrate();
+        assert_eq!(contract.data().owner_id, accounts(1).to_string());
+        assert_eq!(contract.data().epoch_award, DEFAULT_EPOCH_AWARD);
+    }
+
+    #[test]
+    #[should_panic(expected = "Method is private")]
+    fn test_migrate_by_others() {
+        testing_env!(get_context(accounts(0)).build());
+        env::state_write(&v1_fixture());
+
+        testing_env!(get_context(accounts(1)).build());
+        Contract::migrate();
+    }
+}
@@ -0,0 +1,65 @@
+use crate::*;
+use near_sdk::{assert_one_yocto, log, Balance};
+
+#[near_bindgen]
+impl Contract {
+    /// allow spender to transfer up to amount from predecessor's balance,
+    /// overrides previous allowance, 0 removes it.
+    /// Requirements:
+    /// * Caller must attach at least 1 yoctoNEAR, and enough to cover storage of the allowance,
+    ///   the rest is refunded.
+    #[payable]
+    pub fn ft_approve(&mut self, spender_id: ValidAccountId, amount: U128) {
+        assert!(env::attached_deposit() >= 1, "Requires attached deposit of at least 1 yoctoNEAR");
+        let initial_storage_usage = env::storage_usage();
+        let owner_id = env::predecessor_account_id();
+        self.internal_set_allowance(&owner_id, spender_id.as_ref(), amount.into());
+        log!("Approve {} to spend {} sa-vBTC of {}", spender_id, amount.0, owner_id);
+        refund_deposit(initial_storage_usage);
+    }
+
+    /// transfer from owner's balance to receiver, using predecessor's allowance.
+    /// Requirements:
+    /// * Caller must attach exactly 1 yoctoNEAR.
+    #[payable]
+    pub fn ft_transfer_from(
+        &mut self,
+        owner_id: ValidAccountId,
+        receiver_id: ValidAccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        self.assert_not_paused(PAUSE_TRANSFER);
+        let spender_id = env::predecessor_account_id();
+        self.assert_not_frozen(owner_id.as_ref());
+        let amount: Balance = amount.into();
+        let allowance = self.internal_allowance(owner_id.as_ref(), &spender_id);
+        assert!(amount <= allowance, "Not enough allowance, only {} sa-vBTC left", allowance);
+        self.internal_set_allowance(owner_id.as_ref(), &spender_id, allowance - amount);
+        self.internal_checkpoint_balance(owner_id.as_ref());
+        self.internal_checkpoint_balance(receiver_id.as_ref());
+        self.ft.internal_transfer(owner_id.as_ref(), receiver_id.as_ref(), amount, memo);
+        log!("Transfer {} sa-vBTC by {} from allowance of {}", amount, spender_id, owner_id);
+    }
+
+    pub fn ft_allowance(&self, owner_id: ValidAccountId, spender_id: ValidAccountId) -> U128 {
+        self.internal_allowance(owner_id.as_ref(), spender_id.as_ref()).into()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_allowance(&self, owner_id: &AccountId, spender_id: &AccountId) -> Balance {
+        self.data().allowances.get(&(owner_id.clone(), spender_id.clone())).unwrap_or(0)
+    }
+
+    pub(crate) fn internal_set_allowance(&mut self, owner_id: &AccountId, spender_id: &AccountId, amount: Balance) {
+        assert_ne!(owner_id, spender_id, "Owner and spender should be different");
+        let key = (owner_id.clone(), spender_id.clone());
+        if amount > 0 {
+            self.data_mut().allowances.insert(&key, &amount);
+        } else {
+            self.data_mut().allowances.remove(&key);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use crate::*;
+use near_sdk::json_types::U64;
+use near_sdk::log;
+
+#[near_bindgen]
+impl Contract {
+    /// frozen account can't transfer, be transferred from by allowance or redeem,
+    /// its balance stays as is.
+    /// Requirements:
+    /// * The predecessor account must be freezer.
+    pub fn freeze(&mut self, account_id: ValidAccountId, reason: Option<String>) {
+        self.assert_freezer();
+        if self.data_mut().frozen_accounts.insert(account_id.as_ref()) {
+            log!("Freeze {} by {}, reason: {}",
+                account_id, env::predecessor_account_id(), reason.unwrap_or_default());
+        }
+    }
+
+    /// Requirements:
+    /// * The predecessor account must be freezer.
+    pub fn unfreeze(&mut self, account_id: ValidAccountId) {
+        self.assert_freezer();
+        if self.data_mut().frozen_accounts.remove(account_id.as_ref()) {
+            log!("Unfreeze {} by {}", account_id, env::predecessor_account_id());
+        }
+    }
+
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    pub fn add_freezer(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.data_mut().freezers.insert(account_id.as_ref());
+        log!("Add freezer {}", account_id);
+    }
+
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    pub fn remove_freezer(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.data_mut().freezers.remove(account_id.as_ref());
+        log!("Remove freezer {}", account_id);
+    }
+
+    pub fn get_freezers(&self) -> Vec<AccountId> {
+        self.data().freezers.to_vec()
+    }
+
+    pub fn is_frozen(&self, account_id: ValidAccountId) -> bool {
+        self.data().frozen_accounts.contains(account_id.as_ref())
+    }
+
+    pub fn list_frozen_accounts(&self, from_index: U64, limit: U64) -> Vec<AccountId> {
+        let from_index: u64 = from_index.into();
+        let limit: u64 = limit.into();
+        let accounts = self.data().frozen_accounts.as_vector();
+        (from_index..std::cmp::min(from_index + limit, accounts.len()))
+            .map(|index| accounts.get(index).unwrap())
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_not_frozen(&self, account_id: &AccountId) {
+        if self.data().frozen_accounts.contains(account_id) {
+            env::panic(format!("Account {} is frozen", account_id).as_bytes());
+        }
+    }
+
+    fn assert_freezer(&self) {
+        assert!(
+            self.data().freezers.contains(&env::predecessor_account_id()),
+            "Freezer's method");
+    }
+}
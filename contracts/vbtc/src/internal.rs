@@ -0,0 +1,28 @@
+use crate::*;
+use near_sdk::{Balance, Promise, StorageUsage};
+
+pub(crate) fn unique_prefix(prefix: u8, account_id: &AccountId) -> Vec<u8> {
+    let mut ret = Vec::with_capacity(33);
+    ret.push(prefix);
+    ret.extend(env::sha256(account_id.as_bytes()));
+    ret
+}
+
+/// charge storage increased since initial_storage_usage from attached deposit,
+/// and refund the rest to predecessor.
+pub(crate) fn refund_deposit(initial_storage_usage: StorageUsage) {
+    let storage_used = env::storage_usage().saturating_sub(initial_storage_usage);
+    let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
+    let attached_deposit = env::attached_deposit();
+
+    assert!(
+        required_cost <= attached_deposit,
+        "Must attach {} yoctoNEAR to cover storage",
+        required_cost,
+    );
+
+    let refund = attached_deposit - required_cost;
+    if refund > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
+}
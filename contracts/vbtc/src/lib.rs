@@ -8,51 +8,281 @@
 * lib.rs is the main entry point.
 * w_near.rs contains interfaces for depositing and withdrawing
 */
+use std::collections::HashMap;
+
 use near_contract_standards::fungible_token::metadata::{
     FungibleTokenMetadata, FungibleTokenMetadataProvider, FT_METADATA_SPEC,
 };
+use near_contract_standards::fungible_token::core::FungibleTokenCore;
+use near_contract_standards::fungible_token::resolver::FungibleTokenResolver;
 use near_contract_standards::fungible_token::FungibleToken;
+use near_contract_standards::storage_management::{
+    StorageBalance, StorageBalanceBounds, StorageManagement,
+};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::json_types::{ValidAccountId, U128};
-use near_sdk::{env, near_bindgen, AccountId, PanicOnDefault, PromiseOrValue};
+use near_sdk::collections::{LazyOption, LookupMap, LookupSet, UnorderedMap, UnorderedSet, Vector};
+use near_sdk::json_types::{Base58PublicKey, ValidAccountId, U128};
+use near_sdk::{env, log, near_bindgen, AccountId, Balance, PanicOnDefault, PromiseOrValue};
 
+mod internal;
+mod owner;
 mod v_btc;
+mod pause;
+mod upgrade;
+mod redeem;
+mod allowance;
+mod permit;
+mod freeze;
+mod snapshot;
+mod vesting;
+
+use crate::internal::*;
+use crate::upgrade::ContractDataV2;
+pub use crate::pause::*;
+pub use crate::redeem::*;
+pub use crate::snapshot::*;
+pub use crate::v_btc::*;
+pub use crate::vesting::*;
 
 near_sdk::setup_alloc!();
 
+/// 21 million BTC in satoshi
+pub const DEFAULT_MAX_SUPPLY: Balance = 21_000_000 * 100_000_000;
+
+/// state of this contract except the fungible token itself,
+/// wrapped in VersionedContractData
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractData {
+    /// owner manages minters, relayers, pause and upgrade
+    pub owner_id: AccountId,
+    /// account proposed by owner to take over ownership, waiting for its acceptance
+    pub proposed_owner_id: Option<AccountId>,
+    /// accounts allowed to mint, each with its own quota
+    pub minters: UnorderedMap<AccountId, MinterQuota>,
+    /// idempotency keys of mints already done, as "{minter}:{key}"
+    pub used_mint_keys: LookupSet<String>,
+    /// paused features, see PAUSE_* flags
+    pub paused: PauseFlags,
+    /// total supply can never exceed it, burned vBTC could be minted again
+    pub max_supply: Balance,
+
+    /// accounts that pay BTC for redemptions and mark them fulfilled
+    pub relayers: UnorderedSet<AccountId>,
+    /// all redemptions, indexed by id
+    pub redemptions: Vector<Redemption>,
+    /// user -> ids of redemptions
+    pub redemptions_per_account: LookupMap<AccountId, Vector<u64>>,
+
+    /// (owner, spender) -> amount spender can still transfer from owner
+    pub allowances: LookupMap<(AccountId, AccountId), Balance>,
+    /// public keys whose signatures authorize ft_permit, and their reverse index
+    pub permit_keys: LookupMap<AccountId, Base58PublicKey>,
+    pub permit_key_owners: LookupMap<Base58PublicKey, AccountId>,
+    /// user -> nonce of next permit
+    pub permit_nonces: LookupMap<AccountId, u64>,
+
+    /// NEP-148 metadata, owner can update icon and reference
+    pub metadata: LazyOption<FungibleTokenMetadata>,
+
+    /// accounts that can freeze and unfreeze others
+    pub freezers: UnorderedSet<AccountId>,
+    pub frozen_accounts: UnorderedSet<AccountId>,
+
+    /// snapshots taken by owner, see snapshot.rs
+    pub snapshots: Vector<Snapshot>,
+    /// user -> balances at snapshots, written lazily on balance changes,
+    /// at most one per snapshot, storage paid by this contract
+    pub balance_checkpoints: LookupMap<AccountId, Vector<Checkpoint>>,
+    pub supply_checkpoints: Vector<Checkpoint>,
+
+    /// all vesting schedules, indexed by id, locked vBTC is held by this contract
+    pub vestings: Vector<Vesting>,
+    /// receiver -> ids of vestings
+    pub vestings_per_account: LookupMap<AccountId, Vector<u64>>,
+}
+
+/// versioned contract data, so that state layout can evolve with upgrades,
+/// see migrate for how old layouts are converted.
+/// When fields are added, the layout released before is frozen as a new variant
+/// in front of Current, so that tags of released layouts never change.
+#[derive(BorshDeserialize, BorshSerialize)]
+#[allow(clippy::large_enum_variant)]
+pub enum VersionedContractData {
+    V2(ContractDataV2),
+    Current(ContractData),
+}
+
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct Contract {
+    /// layout of the token is kept by near-contract-standards, so it stays out of versioned data
     pub ft: FungibleToken,
 
-    pub minter_id: AccountId,
+    data: VersionedContractData,
 }
 
 #[near_bindgen]
 impl Contract {
     #[init]
-    pub fn new(minter_id: ValidAccountId) -> Self {
+    pub fn new(owner_id: ValidAccountId, max_supply: Option<U128>) -> Self {
         assert!(!env::state_exists(), "Already initialized");
         Self {
             ft: FungibleToken::new(b"a".to_vec()),
-            minter_id: minter_id.into(),
+            data: VersionedContractData::Current(ContractData {
+                owner_id: owner_id.into(),
+                proposed_owner_id: None,
+                minters: UnorderedMap::new(b"f".to_vec()),
+                used_mint_keys: LookupSet::new(b"g".to_vec()),
+                paused: 0,
+                max_supply: max_supply.map(|v| v.into()).unwrap_or(DEFAULT_MAX_SUPPLY),
+                relayers: UnorderedSet::new(b"b".to_vec()),
+                redemptions: Vector::new(b"c".to_vec()),
+                redemptions_per_account: LookupMap::new(b"d".to_vec()),
+                allowances: LookupMap::new(b"h".to_vec()),
+                permit_keys: LookupMap::new(b"i".to_vec()),
+                permit_key_owners: LookupMap::new(b"j".to_vec()),
+                permit_nonces: LookupMap::new(b"k".to_vec()),
+                metadata: LazyOption::new(b"l".to_vec(), Some(&default_metadata())),
+                freezers: UnorderedSet::new(b"m".to_vec()),
+                frozen_accounts: UnorderedSet::new(b"n".to_vec()),
+                snapshots: Vector::new(b"o".to_vec()),
+                balance_checkpoints: LookupMap::new(b"p".to_vec()),
+                supply_checkpoints: Vector::new(b"q".to_vec()),
+                vestings: Vector::new(b"s".to_vec()),
+                vestings_per_account: LookupMap::new(b"t".to_vec()),
+            }),
         }
     }
 }
 
-near_contract_standards::impl_fungible_token_core!(Contract, ft);
-near_contract_standards::impl_fungible_token_storage!(Contract, ft);
+impl Contract {
+    pub(crate) fn data(&self) -> &ContractData {
+        match &self.data {
+            VersionedContractData::Current(data) => data,
+            _ => env::panic(b"State needs migrate"),
+        }
+    }
+
+    pub(crate) fn data_mut(&mut self) -> &mut ContractData {
+        match &mut self.data {
+            VersionedContractData::Current(data) => data,
+            _ => env::panic(b"State needs migrate"),
+        }
+    }
+}
 
+#[near_bindgen]
+impl FungibleTokenCore for Contract {
+    #[payable]
+    fn ft_transfer(&mut self, receiver_id: ValidAccountId, amount: U128, memo: Option<String>) {
+        self.assert_not_paused(PAUSE_TRANSFER);
+        self.assert_not_frozen(&env::predecessor_account_id());
+        self.internal_checkpoint_balance(&env::predecessor_account_id());
+        self.internal_checkpoint_balance(receiver_id.as_ref());
+        self.ft.ft_transfer(receiver_id, amount, memo)
+    }
+
+    #[payable]
+    fn ft_transfer_call(
+        &mut self,
+        receiver_id: ValidAccountId,
+        amount: U128,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        self.assert_not_paused(PAUSE_TRANSFER);
+        self.assert_not_frozen(&env::predecessor_account_id());
+        self.internal_checkpoint_balance(&env::predecessor_account_id());
+        self.internal_checkpoint_balance(receiver_id.as_ref());
+        self.ft.ft_transfer_call(receiver_id, amount, memo, msg)
+    }
+
+    fn ft_total_supply(&self) -> U128 {
+        self.ft.ft_total_supply()
+    }
+
+    fn ft_balance_of(&self, account_id: ValidAccountId) -> U128 {
+        self.ft.ft_balance_of(account_id)
+    }
+}
+
+#[near_bindgen]
+impl FungibleTokenResolver for Contract {
+    /// refund of ft_transfer_call is not blocked by pause,
+    /// so tokens never get stuck in receiver.
+    #[private]
+    fn ft_resolve_transfer(
+        &mut self,
+        sender_id: ValidAccountId,
+        receiver_id: ValidAccountId,
+        amount: U128,
+    ) -> U128 {
+        self.internal_checkpoint_balance(sender_id.as_ref());
+        self.internal_checkpoint_balance(receiver_id.as_ref());
+        // refund is burned if sender unregistered meanwhile
+        self.internal_checkpoint_supply();
+        self.ft.ft_resolve_transfer(sender_id, receiver_id, amount)
+    }
+}
+
+/// same as impl_fungible_token_storage! of near-contract-standards,
+/// except that storage_unregister writes snapshot checkpoints for burned balance.
+#[near_bindgen]
+impl StorageManagement for Contract {
+    #[payable]
+    fn storage_deposit(
+        &mut self,
+        account_id: Option<ValidAccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        self.ft.storage_deposit(account_id, registration_only)
+    }
+
+    #[payable]
+    fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        self.ft.storage_withdraw(amount)
+    }
+
+    /// balance of a force unregistered account is burned,
+    /// checkpoints take values from before that.
+    #[payable]
+    fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        if let Some((account_id, balance)) = self.ft.internal_storage_unregister(force) {
+            self.internal_write_balance_checkpoint(&account_id, balance);
+            let total_supply = self.ft.total_supply;
+            self.internal_write_supply_checkpoint(total_supply + balance);
+            log!("Closed {} with {} sa-vBTC burned", account_id, balance);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        self.ft.storage_balance_bounds()
+    }
+
+    fn storage_balance_of(&self, account_id: ValidAccountId) -> Option<StorageBalance> {
+        self.ft.storage_balance_of(account_id)
+    }
+}
+
+/// metadata before owner sets icon and reference
+pub(crate) fn default_metadata() -> FungibleTokenMetadata {
+    FungibleTokenMetadata {
+        spec: FT_METADATA_SPEC.to_string(),
+        name: String::from("virtual BTC fungible token"),
+        symbol: String::from("vBTC"),
+        icon: None,
+        reference: None,
+        reference_hash: None,
+        decimals: 8,
+    }
+}
+
+#[near_bindgen]
 impl FungibleTokenMetadataProvider for Contract {
     fn ft_metadata(&self) -> FungibleTokenMetadata {
-        FungibleTokenMetadata {
-            spec: FT_METADATA_SPEC.to_string(),
-            name: String::from("virtual BTC fungible token"),
-            symbol: String::from("vBTC"),
-            icon: None,
-            reference: None,
-            reference_hash: None,
-            decimals: 8,
-        }
+        self.data().metadata.get().unwrap()
     }
 }
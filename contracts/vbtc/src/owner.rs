@@ -0,0 +1,65 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::log;
+
+#[near_bindgen]
+impl Contract {
+    /// first step of ownership transfer,
+    /// the proposed account must call accept_owner to become the owner.
+    /// Proposing again overrides the previous proposal.
+    pub fn propose_owner(&mut self, new_owner_id: ValidAccountId) {
+        self.assert_owner();
+        self.data_mut().proposed_owner_id = Some(new_owner_id.as_ref().clone());
+        log!("Owner {} proposed {} as new owner", self.data().owner_id, new_owner_id);
+    }
+
+    /// second step of ownership transfer, called by the proposed owner
+    pub fn accept_owner(&mut self) {
+        let caller = env::predecessor_account_id();
+        assert_eq!(
+            Some(&caller),
+            self.data().proposed_owner_id.as_ref(),
+            "Only proposed owner can accept");
+        log!("Ownership transferred from {} to {}", self.data().owner_id, caller);
+        self.data_mut().owner_id = caller;
+        self.data_mut().proposed_owner_id = None;
+    }
+
+    /// update icon, reference and reference_hash of ft_metadata,
+    /// None clears the field.
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    /// * reference and reference_hash must be both set or both None.
+    pub fn set_metadata(
+        &mut self,
+        icon: Option<String>,
+        reference: Option<String>,
+        reference_hash: Option<Base64VecU8>,
+    ) {
+        self.assert_owner();
+        let mut metadata = self.data().metadata.get().unwrap();
+        metadata.icon = icon;
+        metadata.reference = reference;
+        metadata.reference_hash = reference_hash;
+        metadata.assert_valid();
+        self.data_mut().metadata.set(&metadata);
+        log!("Update metadata of vBTC");
+    }
+
+    pub fn get_owner(&self) -> AccountId {
+        self.data().owner_id.clone()
+    }
+
+    pub fn get_proposed_owner(&self) -> Option<AccountId> {
+        self.data().proposed_owner_id.clone()
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_owner(&self) {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.data().owner_id,
+            "Owner's method");
+    }
+}
@@ -0,0 +1,44 @@
+use crate::*;
+use near_sdk::log;
+
+/// bit flags of features that can be paused independently
+pub type PauseFlags = u8;
+/// ft_transfer and ft_transfer_call
+pub const PAUSE_TRANSFER: PauseFlags = 1;
+/// mint
+pub const PAUSE_MINT: PauseFlags = 2;
+/// redeem
+pub const PAUSE_REDEEM: PauseFlags = 4;
+
+#[near_bindgen]
+impl Contract {
+    /// pause features in flags, features already paused keep paused.
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    pub fn pause(&mut self, flags: PauseFlags) {
+        self.assert_owner();
+        self.data_mut().paused |= flags;
+        log!("Paused {:#04b}, now {:#04b}", flags, self.data().paused);
+    }
+
+    /// resume features in flags, other paused features keep paused.
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    pub fn unpause(&mut self, flags: PauseFlags) {
+        self.assert_owner();
+        self.data_mut().paused &= !flags;
+        log!("Unpaused {:#04b}, now {:#04b}", flags, self.data().paused);
+    }
+
+    pub fn get_paused(&self) -> PauseFlags {
+        self.data().paused
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_not_paused(&self, flag: PauseFlags) {
+        if self.data().paused & flag != 0 {
+            env::panic(b"This feature is paused");
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::*;
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use near_sdk::json_types::{Base58PublicKey, Base64VecU8, U64};
+use near_sdk::log;
+use std::convert::TryFrom;
+
+/// the message owner signs off-chain to authorize a permit,
+/// signature is made on borsh bytes of this struct.
+#[derive(BorshSerialize)]
+pub struct PermitMessage {
+    /// this contract, so that permit can't be replayed on other tokens
+    pub contract_id: AccountId,
+    pub owner_id: AccountId,
+    pub spender_id: AccountId,
+    pub amount: Balance,
+    pub nonce: u64,
+    /// block timestamp in nanoseconds
+    pub deadline: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// register the ed25519 public key whose signatures act as predecessor in ft_permit,
+    /// replaces the key registered before.
+    /// Requirements:
+    /// * Caller must attach deposit to cover storage of the key, the rest is refunded.
+    #[payable]
+    pub fn register_permit_key(&mut self, public_key: Base58PublicKey) {
+        assert!(
+            public_key.0.len() == 33 && public_key.0[0] == 0,
+            "Only ed25519 public key is supported");
+        let initial_storage_usage = env::storage_usage();
+        let owner_id = env::predecessor_account_id();
+        assert!(
+            self.data().permit_key_owners.get(&public_key).is_none(),
+            "Public key already registered");
+        if let Some(old_key) = self.data_mut().permit_keys.insert(&owner_id, &public_key) {
+            self.data_mut().permit_key_owners.remove(&old_key);
+        }
+        self.data_mut().permit_key_owners.insert(&public_key, &owner_id);
+        log!("Register permit key of {}", owner_id);
+        refund_deposit(initial_storage_usage);
+    }
+
+    /// set allowance of spender on behalf of the owner of owner_pubkey,
+    /// anyone can submit the permit signed by that owner.
+    /// Requirements:
+    /// * nonce must equal to current permit nonce of owner, and the block time is before deadline.
+    /// * signature is ed25519 signature of PermitMessage in borsh.
+    /// * Caller must attach deposit to cover storage of the allowance, the rest is refunded.
+    #[payable]
+    pub fn ft_permit(
+        &mut self,
+        owner_pubkey: Base58PublicKey,
+        spender_id: ValidAccountId,
+        amount: U128,
+        nonce: U64,
+        deadline: U64,
+        signature: Base64VecU8,
+    ) {
+        let initial_storage_usage = env::storage_usage();
+        assert!(env::block_timestamp() <= deadline.0, "Permit expired");
+        let owner_id = self.data().permit_key_owners.get(&owner_pubkey)
+            .expect("Public key not registered");
+        let current_nonce = self.data().permit_nonces.get(&owner_id).unwrap_or(0);
+        assert_eq!(nonce.0, current_nonce, "Invalid permit nonce");
+
+        let message = PermitMessage {
+            contract_id: env::current_account_id(),
+            owner_id: owner_id.clone(),
+            spender_id: spender_id.as_ref().clone(),
+            amount: amount.0,
+            nonce: nonce.0,
+            deadline: deadline.0,
+        };
+        let public_key = PublicKey::from_bytes(&owner_pubkey.0[1..]).expect("Invalid public key");
+        let signature = Signature::try_from(signature.0.as_slice()).expect("Invalid signature");
+        public_key.verify(&message.try_to_vec().unwrap(), &signature).expect("Invalid signature");
+
+        self.data_mut().permit_nonces.insert(&owner_id, &(current_nonce + 1));
+        self.internal_set_allowance(&owner_id, spender_id.as_ref(), amount.0);
+        log!("Permit {} to spend {} sa-vBTC of {} with nonce {}", spender_id, amount.0, owner_id, nonce.0);
+        refund_deposit(initial_storage_usage);
+    }
+
+    pub fn get_permit_key(&self, account_id: ValidAccountId) -> Option<Base58PublicKey> {
+        self.data().permit_keys.get(account_id.as_ref())
+    }
+
+    /// nonce the next permit of the account should use
+    pub fn get_permit_nonce(&self, account_id: ValidAccountId) -> U64 {
+        self.data().permit_nonces.get(account_id.as_ref()).unwrap_or(0).into()
+    }
+}
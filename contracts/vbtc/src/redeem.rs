@@ -0,0 +1,163 @@
+use crate::*;
+use near_sdk::collections::Vector;
+use near_sdk::json_types::U64;
+use near_sdk::serde::Serialize;
+use near_sdk::{log, Balance, Timestamp};
+
+/// 0 - pending, 1 - fulfilled
+pub type RedemptionStatus = u8;
+pub const RS_PENDING: RedemptionStatus = 0;
+pub const RS_FULFILLED: RedemptionStatus = 1;
+
+/// shortest legacy address and longest bech32 address
+const MIN_BTC_ADDRESS_LEN: usize = 26;
+const MAX_BTC_ADDRESS_LEN: usize = 90;
+
+/// a request to get real BTC back for burned vBTC,
+/// id is its index in redemptions.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Redemption {
+    pub account_id: AccountId,
+    pub amount: Balance,
+    pub btc_address: String,
+    pub status: RedemptionStatus,
+    /// set by relayer when fulfilled
+    pub btc_txid: Option<String>,
+    pub created_at: Timestamp,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableRedemption {
+    pub id: U64,
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub btc_address: String,
+    pub status: RedemptionStatus,
+    pub btc_txid: Option<String>,
+    pub created_at: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// burn vbtc and request the same amount of BTC sent to btc_address.
+    /// Requirements:
+    /// * Caller must attach deposit to cover storage of the request, the rest is refunded.
+    /// * Returns id of the redemption.
+    #[payable]
+    pub fn redeem(&mut self, amount: U128, btc_address: String) -> U64 {
+        self.assert_not_paused(PAUSE_REDEEM);
+        let initial_storage_usage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        self.assert_not_frozen(&account_id);
+        let amount: Balance = amount.into();
+        assert!(amount > 0, "The amount should be a positive number");
+        assert!(
+            btc_address.len() >= MIN_BTC_ADDRESS_LEN && btc_address.len() <= MAX_BTC_ADDRESS_LEN,
+            "Invalid BTC address"
+        );
+
+        self.internal_checkpoint_balance(&account_id);
+        self.internal_checkpoint_supply();
+        self.ft.internal_withdraw(&account_id, amount);
+        log!("Burn {} sa-vBTC from {}", amount, account_id);
+
+        let id = self.data().redemptions.len();
+        self.data_mut().redemptions.push(&Redemption {
+            account_id: account_id.clone(),
+            amount,
+            btc_address: btc_address.clone(),
+            status: RS_PENDING,
+            btc_txid: None,
+            created_at: env::block_timestamp(),
+        });
+        let mut account_redemptions = self.data().redemptions_per_account.get(&account_id)
+            .unwrap_or_else(|| Vector::new(unique_prefix(b'e', &account_id)));
+        account_redemptions.push(&id);
+        self.data_mut().redemptions_per_account.insert(&account_id, &account_redemptions);
+        log!("Redemption {} of {} sa-vBTC from {} to {}", id, amount, account_id, btc_address);
+
+        refund_deposit(initial_storage_usage);
+        id.into()
+    }
+
+    /// mark a pending redemption as fulfilled with the BTC transaction paid to user.
+    /// Requirements:
+    /// * The predecessor account must be relayer.
+    pub fn fulfill_redemption(&mut self, id: U64, btc_txid: String) {
+        assert!(
+            self.data().relayers.contains(&env::predecessor_account_id()),
+            "Relayer's method");
+        let id: u64 = id.into();
+        let mut redemption = self.data().redemptions.get(id).expect("Redemption not found");
+        assert_eq!(redemption.status, RS_PENDING, "Redemption is not pending");
+        redemption.status = RS_FULFILLED;
+        redemption.btc_txid = Some(btc_txid.clone());
+        self.data_mut().redemptions.replace(id, &redemption);
+        log!("Redemption {} fulfilled by {} in BTC tx {}", id, env::predecessor_account_id(), btc_txid);
+    }
+
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    pub fn add_relayer(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.data_mut().relayers.insert(account_id.as_ref());
+        log!("Add relayer {}", account_id);
+    }
+
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    pub fn remove_relayer(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.data_mut().relayers.remove(account_id.as_ref());
+        log!("Remove relayer {}", account_id);
+    }
+
+    pub fn get_relayers(&self) -> Vec<AccountId> {
+        self.data().relayers.to_vec()
+    }
+
+    pub fn get_redemption(&self, id: U64) -> Option<HumanReadableRedemption> {
+        let id: u64 = id.into();
+        self.data().redemptions.get(id).map(|redemption| redemption_view(id, redemption))
+    }
+
+    /// list all redemptions by id
+    pub fn list_redemptions(&self, from_index: U64, limit: U64) -> Vec<HumanReadableRedemption> {
+        let from_index: u64 = from_index.into();
+        let limit: u64 = limit.into();
+        let redemptions = &self.data().redemptions;
+        (from_index..std::cmp::min(from_index + limit, redemptions.len()))
+            .map(|id| redemption_view(id, redemptions.get(id).unwrap()))
+            .collect()
+    }
+
+    /// list redemptions requested by the account
+    pub fn list_redemptions_by_account(&self, account_id: ValidAccountId,
+        from_index: U64, limit: U64
+    ) -> Vec<HumanReadableRedemption> {
+        let from_index: u64 = from_index.into();
+        let limit: u64 = limit.into();
+        match self.data().redemptions_per_account.get(account_id.as_ref()) {
+            Some(ids) => (from_index..std::cmp::min(from_index + limit, ids.len()))
+                .map(|index| {
+                    let id = ids.get(index).unwrap();
+                    redemption_view(id, self.data().redemptions.get(id).unwrap())
+                })
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+fn redemption_view(id: u64, redemption: Redemption) -> HumanReadableRedemption {
+    HumanReadableRedemption {
+        id: id.into(),
+        account_id: redemption.account_id,
+        amount: redemption.amount.into(),
+        btc_address: redemption.btc_address,
+        status: redemption.status,
+        btc_txid: redemption.btc_txid,
+        created_at: redemption.created_at.into(),
+    }
+}
@@ -0,0 +1,169 @@
+use crate::*;
+use near_sdk::collections::Vector;
+use near_sdk::json_types::U64;
+use near_sdk::serde::Serialize;
+use near_sdk::{log, BlockHeight, Timestamp};
+
+/// upper limit of snapshots, which bounds checkpoint storage paid by this contract,
+/// see snapshot.
+pub const MAX_SNAPSHOTS: u64 = 1000;
+
+/// a point in time whose balances can be queried later,
+/// id of a snapshot is its index in snapshots plus 1.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Snapshot {
+    pub block_height: BlockHeight,
+    pub timestamp: Timestamp,
+    /// mining epoch of miner nft contract given by owner as a label,
+    /// not checked against the nft contract
+    pub epoch: Option<u64>,
+}
+
+/// balance at snapshot_id, written at the first balance change after that snapshot,
+/// so it also covers earlier snapshots since the previous checkpoint.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Checkpoint {
+    pub snapshot_id: u64,
+    pub balance: Balance,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableSnapshot {
+    pub id: U64,
+    pub block_height: U64,
+    pub timestamp: U64,
+    pub epoch: Option<U64>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// take a snapshot of all balances and total supply at current block,
+    /// returns id of the snapshot.
+    /// epoch is only stored as a label of this snapshot, it is not checked,
+    /// and the snapshot is always taken at current block, not at that epoch.
+    /// Checkpoints are written at the first balance change of each account after a snapshot,
+    /// at the storage cost of this contract, as ft_transfer attaches only 1 yoctoⓃ.
+    /// So storage grows by at most one checkpoint per registered account per snapshot,
+    /// and snapshots are limited to MAX_SNAPSHOTS.
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    /// * Less than MAX_SNAPSHOTS snapshots taken.
+    pub fn snapshot(&mut self, epoch: Option<U64>) -> U64 {
+        self.assert_owner();
+        assert!(self.current_snapshot_id() < MAX_SNAPSHOTS, "Too many snapshots");
+        self.data_mut().snapshots.push(&Snapshot {
+            block_height: env::block_index(),
+            timestamp: env::block_timestamp(),
+            epoch: epoch.map(|v| v.into()),
+        });
+        let id = self.current_snapshot_id();
+        log!("Snapshot {} at block {}", id, env::block_index());
+        id.into()
+    }
+
+    pub fn get_snapshot(&self, snapshot_id: U64) -> Option<HumanReadableSnapshot> {
+        let snapshot_id: u64 = snapshot_id.into();
+        if snapshot_id == 0 {
+            return None;
+        }
+        self.data().snapshots.get(snapshot_id - 1).map(|snapshot| HumanReadableSnapshot {
+            id: snapshot_id.into(),
+            block_height: snapshot.block_height.into(),
+            timestamp: snapshot.timestamp.into(),
+            epoch: snapshot.epoch.map(|v| v.into()),
+        })
+    }
+
+    pub fn get_current_snapshot_id(&self) -> U64 {
+        self.current_snapshot_id().into()
+    }
+
+    pub fn balance_of_at(&self, account_id: ValidAccountId, snapshot_id: U64) -> U128 {
+        let snapshot_id = self.assert_snapshot_id(snapshot_id.into());
+        self.data().balance_checkpoints.get(account_id.as_ref())
+            .and_then(|checkpoints| find_checkpoint(&checkpoints, snapshot_id))
+            .unwrap_or_else(|| self.ft.accounts.get(account_id.as_ref()).unwrap_or(0))
+            .into()
+    }
+
+    pub fn total_supply_at(&self, snapshot_id: U64) -> U128 {
+        let snapshot_id = self.assert_snapshot_id(snapshot_id.into());
+        find_checkpoint(&self.data().supply_checkpoints, snapshot_id)
+            .unwrap_or(self.ft.total_supply)
+            .into()
+    }
+}
+
+impl Contract {
+    fn current_snapshot_id(&self) -> u64 {
+        self.data().snapshots.len()
+    }
+
+    fn assert_snapshot_id(&self, snapshot_id: u64) -> u64 {
+        assert!(
+            snapshot_id > 0 && snapshot_id <= self.current_snapshot_id(),
+            "Snapshot doesn't exist"
+        );
+        snapshot_id
+    }
+
+    /// must be called before balance of account changes
+    pub(crate) fn internal_checkpoint_balance(&mut self, account_id: &AccountId) {
+        let balance = self.ft.accounts.get(account_id).unwrap_or(0);
+        self.internal_write_balance_checkpoint(account_id, balance);
+    }
+
+    /// must be called before total supply changes
+    pub(crate) fn internal_checkpoint_supply(&mut self) {
+        let total_supply = self.ft.total_supply;
+        self.internal_write_supply_checkpoint(total_supply);
+    }
+
+    /// balance before the change is given by caller,
+    /// for changes done inside near-contract-standards where it can't be read in advance.
+    pub(crate) fn internal_write_balance_checkpoint(&mut self, account_id: &AccountId, balance: Balance) {
+        let snapshot_id = self.current_snapshot_id();
+        if snapshot_id == 0 {
+            return;
+        }
+        let mut checkpoints = self.data().balance_checkpoints.get(account_id)
+            .unwrap_or_else(|| Vector::new(unique_prefix(b'r', account_id)));
+        if push_checkpoint(&mut checkpoints, snapshot_id, balance) {
+            self.data_mut().balance_checkpoints.insert(account_id, &checkpoints);
+        }
+    }
+
+    pub(crate) fn internal_write_supply_checkpoint(&mut self, total_supply: Balance) {
+        let snapshot_id = self.current_snapshot_id();
+        if snapshot_id > 0 {
+            push_checkpoint(&mut self.data_mut().supply_checkpoints, snapshot_id, total_supply);
+        }
+    }
+}
+
+/// only the first change after a snapshot writes a checkpoint,
+/// returns whether it is written.
+fn push_checkpoint(checkpoints: &mut Vector<Checkpoint>, snapshot_id: u64, balance: Balance) -> bool {
+    let len = checkpoints.len();
+    if len > 0 && checkpoints.get(len - 1).unwrap().snapshot_id >= snapshot_id {
+        return false;
+    }
+    checkpoints.push(&Checkpoint { snapshot_id, balance });
+    true
+}
+
+/// balance of the first checkpoint at or after snapshot_id,
+/// None means no change since then, so current balance applies.
+fn find_checkpoint(checkpoints: &Vector<Checkpoint>, snapshot_id: u64) -> Option<Balance> {
+    let (mut low, mut high) = (0, checkpoints.len());
+    while low < high {
+        let mid = (low + high) / 2;
+        if checkpoints.get(mid).unwrap().snapshot_id < snapshot_id {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    checkpoints.get(low).map(|checkpoint| checkpoint.balance)
+}
@@ -0,0 +1,185 @@
+use crate::*;
+use near_sdk::{log, Gas, Promise};
+
+/// gas kept for upgrade itself, the rest is given to migrate
+const GAS_FOR_UPGRADE_CALL: Gas = 50_000_000_000_000;
+
+/// Contract layout deployed before state versioning,
+/// the whole struct was stored as raw borsh, without VersionedContractData.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractV1 {
+    pub ft: FungibleToken,
+    pub minter_id: AccountId,
+}
+
+/// Contract data layout of the first versioned state,
+/// with the single minter, its two-step rotation and pause flags.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractDataV2 {
+    pub minter_id: AccountId,
+    pub proposed_minter_id: Option<AccountId>,
+    pub paused: PauseFlags,
+}
+
+impl From<ContractV1> for Contract {
+    fn from(v1: ContractV1) -> Self {
+        Contract {
+            ft: v1.ft,
+            data: VersionedContractData::Current(ContractDataV2 {
+                minter_id: v1.minter_id,
+                proposed_minter_id: None,
+                paused: 0,
+            }.into()),
+        }
+    }
+}
+
+/// the single minter of v2 becomes owner, and stays as a minter without quota
+impl From<ContractDataV2> for ContractData {
+    fn from(v2: ContractDataV2) -> Self {
+        let mut minters = UnorderedMap::new(b"f".to_vec());
+        minters.insert(&v2.minter_id, &MinterQuota {
+            quota: None,
+            period: DEFAULT_QUOTA_PERIOD,
+            period_start: env::block_timestamp(),
+            minted_in_period: 0,
+        });
+        ContractData {
+            owner_id: v2.minter_id,
+            proposed_owner_id: v2.proposed_minter_id,
+            minters,
+            used_mint_keys: LookupSet::new(b"g".to_vec()),
+            paused: v2.paused,
+            max_supply: DEFAULT_MAX_SUPPLY,
+            relayers: UnorderedSet::new(b"b".to_vec()),
+            redemptions: Vector::new(b"c".to_vec()),
+            redemptions_per_account: LookupMap::new(b"d".to_vec()),
+            allowances: LookupMap::new(b"h".to_vec()),
+            permit_keys: LookupMap::new(b"i".to_vec()),
+            permit_key_owners: LookupMap::new(b"j".to_vec()),
+            permit_nonces: LookupMap::new(b"k".to_vec()),
+            metadata: LazyOption::new(b"l".to_vec(), Some(&default_metadata())),
+            freezers: UnorderedSet::new(b"m".to_vec()),
+            frozen_accounts: UnorderedSet::new(b"n".to_vec()),
+            snapshots: Vector::new(b"o".to_vec()),
+            balance_checkpoints: LookupMap::new(b"p".to_vec()),
+            supply_checkpoints: Vector::new(b"q".to_vec()),
+            vestings: Vector::new(b"s".to_vec()),
+            vestings_per_account: LookupMap::new(b"t".to_vec()),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Convert state written by previous code into current layout,
+    /// should be called by this contract itself right after new code deployed.
+    /// In raw ContractV1 state, the token is followed by the length of minter_id,
+    /// which is at least 2, so it would never be taken as a tag of VersionedContractData.
+    #[init(ignore_state)]
+    #[private]
+    pub fn migrate() -> Self {
+        let state = env::storage_read(b"STATE").expect("Not initialized");
+        if let Ok(Contract { ft, data }) = Contract::try_from_slice(&state) {
+            let data = match data {
+                VersionedContractData::V2(v2) => {
+                    log!("Migrate state from v2");
+                    VersionedContractData::Current(v2.into())
+                }
+                data => data,
+            };
+            return Contract { ft, data };
+        }
+        let v1 = ContractV1::try_from_slice(&state).expect("Unknown state layout");
+        log!("Migrate state from v1");
+        v1.into()
+    }
+
+    /// Deploy new code to this contract, then call migrate on it in the same batch,
+    /// so the code and the state are upgraded together or not at all.
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    /// * Code is passed as borsh raw bytes rather than json.
+    pub fn upgrade(&self, #[serializer(borsh)] code: Vec<u8>) -> Promise {
+        self.assert_owner();
+        log!("Upgrade to code with {} bytes", code.len());
+        Promise::new(env::current_account_id())
+            .deploy_contract(code)
+            .function_call(
+                b"migrate".to_vec(),
+                vec![],
+                0,
+                env::prepaid_gas() - env::used_gas() - GAS_FOR_UPGRADE_CALL,
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::{accounts, VMContextBuilder};
+    use near_sdk::{testing_env, MockedBlockchain};
+
+    fn get_context(predecessor: ValidAccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .predecessor_account_id(predecessor);
+        builder
+    }
+
+    #[test]
+    fn test_migrate_from_v1() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut v1 = ContractV1 {
+            ft: FungibleToken::new(b"a".to_vec()),
+            minter_id: accounts(1).into(),
+        };
+        v1.ft.internal_register_account(accounts(3).as_ref());
+        v1.ft.internal_deposit(accounts(3).as_ref(), 100);
+        env::state_write(&v1);
+
+        let contract = Contract::migrate();
+        assert_eq!(contract.data().owner_id, accounts(1).to_string());
+        assert_eq!(contract.data().proposed_owner_id, None);
+        assert!(contract.data().minters.get(&accounts(1).into()).unwrap().quota.is_none());
+        assert_eq!(contract.data().paused, 0);
+        assert_eq!(contract.data().max_supply, DEFAULT_MAX_SUPPLY);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 100);
+        assert_eq!(contract.ft_total_supply().0, 100);
+    }
+
+    #[test]
+    fn test_migrate_from_v2() {
+        testing_env!(get_context(accounts(0)).build());
+        let mut ft = FungibleToken::new(b"a".to_vec());
+        ft.internal_register_account(accounts(3).as_ref());
+        ft.internal_deposit(accounts(3).as_ref(), 100);
+        env::state_write(&Contract {
+            ft,
+            data: VersionedContractData::V2(ContractDataV2 {
+                minter_id: accounts(1).into(),
+                proposed_minter_id: Some(accounts(2).into()),
+                paused: PAUSE_MINT,
+            }),
+        });
+
+        let contract = Contract::migrate();
+        assert_eq!(contract.data().owner_id, accounts(1).to_string());
+        assert_eq!(contract.data().proposed_owner_id, Some(accounts(2).into()));
+        assert!(contract.data().minters.get(&accounts(1).into()).unwrap().quota.is_none());
+        assert_eq!(contract.data().paused, PAUSE_MINT);
+        assert_eq!(contract.ft_balance_of(accounts(3)).0, 100);
+    }
+
+    #[test]
+    fn test_migrate_current_state() {
+        testing_env!(get_context(accounts(0)).build());
+        let contract = Contract::new(accounts(1), None);
+        env::state_write(&contract);
+
+        let contract = Contract::migrate();
+        assert_eq!(contract.data().owner_id, accounts(1).to_string());
+        assert_eq!(contract.data().paused, 0);
+    }
+}
@@ -1,24 +1,191 @@
 use crate::*;
-use near_sdk::{env, log, Balance};
+use near_sdk::json_types::U64;
+use near_sdk::serde::Serialize;
+use near_sdk::{env, log, Balance, Timestamp};
+
+/// one day in nanoseconds
+pub const DEFAULT_QUOTA_PERIOD: u64 = 24 * 3600 * 1_000_000_000;
+/// mint key is stored forever, so keep it short
+const MAX_MINT_KEY_LEN: usize = 64;
+
+/// how much a minter can mint in each period,
+/// minted_in_period resets once a period passed since period_start.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MinterQuota {
+    /// None means unlimited
+    pub quota: Option<Balance>,
+    /// period length in nanoseconds
+    pub period: u64,
+    pub period_start: Timestamp,
+    pub minted_in_period: Balance,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableMinterQuota {
+    pub quota: Option<U128>,
+    pub period: U64,
+    pub period_start: U64,
+    pub minted_in_period: U128,
+}
+
+impl From<MinterQuota> for HumanReadableMinterQuota {
+    fn from(minter: MinterQuota) -> Self {
+        HumanReadableMinterQuota {
+            quota: minter.quota.map(|v| v.into()),
+            period: minter.period.into(),
+            period_start: minter.period_start.into(),
+            minted_in_period: minter.minted_in_period.into(),
+        }
+    }
+}
 
 #[near_bindgen]
 impl Contract {
     /// mint vbtc.
     /// Requirements:
-    /// * The predecessor account must be minter.
+    /// * The predecessor account must be a minter, and has quota left in current period.
     /// * No need to deposit near, cause minter would care about the storage fee of this contract.
     /// * If account is not registered, will be auto registered.
-    pub fn mint(&mut self, amount: Balance, receiver_id: ValidAccountId) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.minter_id,
-             "Only minter can mint vBTC");
-        if !self.ft.accounts.contains_key(receiver_id.as_ref()) {
-            // Not registered, register
-            self.ft.internal_register_account(receiver_id.as_ref());
+    /// * Total supply after mint can not exceed max_supply.
+    /// * If key is given, such as `epoch:1234`, it can only be used once,
+    ///   so that a retried payout never mints twice.
+    pub fn mint(&mut self, amount: U128, receiver_id: ValidAccountId, key: Option<String>) {
+        let amount: Balance = amount.into();
+        self.assert_not_paused(PAUSE_MINT);
+        if let Some(key) = key {
+            self.internal_use_mint_key(&env::predecessor_account_id(), key);
         }
-        self.ft.internal_deposit(receiver_id.as_ref(), amount);
+        self.internal_use_quota(&env::predecessor_account_id(), amount);
+        self.assert_mintable(amount);
+        self.internal_mint(receiver_id.as_ref(), amount);
         log!("Mint {} sa-vBTC to {}", amount, receiver_id);
     }
 
+    /// mint vbtc to many receivers at once, such as all members of a mining pool.
+    /// Requirements are the same as mint, quota and max_supply are checked against the sum.
+    pub fn mint_batch(&mut self, receivers: Vec<(ValidAccountId, U128)>, key: Option<String>) {
+        self.assert_not_paused(PAUSE_MINT);
+        assert!(!receivers.is_empty(), "No receivers to mint");
+        if let Some(key) = key {
+            self.internal_use_mint_key(&env::predecessor_account_id(), key);
+        }
+        let total: Balance = receivers.iter()
+            .map(|(_, amount)| amount.0)
+            .fold(0, |sum, amount| sum.checked_add(amount).expect("Amount overflow"));
+        self.internal_use_quota(&env::predecessor_account_id(), total);
+        self.assert_mintable(total);
+        for (receiver_id, amount) in receivers.iter() {
+            self.internal_mint(receiver_id.as_ref(), amount.0);
+        }
+        log!("Mint {} sa-vBTC in batch to {}", total,
+            receivers.iter()
+                .map(|(receiver_id, amount)| format!("{}:{}", receiver_id, amount.0))
+                .collect::<Vec<_>>()
+                .join(","));
+    }
+
+    /// add a minter or update quota of an existing minter,
+    /// updating quota keeps what was minted in current period.
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    /// * quota None means unlimited, period defaults to one day in nanoseconds.
+    pub fn set_minter(&mut self, account_id: ValidAccountId, quota: Option<U128>, period: Option<U64>) {
+        self.assert_owner();
+        let mut minter = self.data().minters.get(account_id.as_ref()).unwrap_or(MinterQuota {
+            quota: None,
+            period: DEFAULT_QUOTA_PERIOD,
+            period_start: env::block_timestamp(),
+            minted_in_period: 0,
+        });
+        minter.quota = quota.map(|v| v.into());
+        minter.period = period.map(|v| v.into()).unwrap_or(DEFAULT_QUOTA_PERIOD);
+        assert!(minter.period > 0, "Quota period should be positive");
+        self.data_mut().minters.insert(account_id.as_ref(), &minter);
+        log!("Set minter {} with quota {:?} per {} ns", account_id, minter.quota, minter.period);
+    }
+
+    /// Requirements:
+    /// * The predecessor account must be owner.
+    pub fn remove_minter(&mut self, account_id: ValidAccountId) {
+        self.assert_owner();
+        self.data_mut().minters.remove(account_id.as_ref()).expect("Minter not found");
+        log!("Remove minter {}", account_id);
+    }
+
+    pub fn get_minter(&self, account_id: ValidAccountId) -> Option<HumanReadableMinterQuota> {
+        self.data().minters.get(account_id.as_ref()).map(|minter| minter.into())
+    }
+
+    pub fn get_minters(&self) -> HashMap<AccountId, HumanReadableMinterQuota> {
+        self.data().minters.iter().map(|(account_id, minter)| (account_id, minter.into())).collect()
+    }
+
+    /// mint keys are scoped to each minter
+    pub fn is_mint_key_used(&self, minter_id: ValidAccountId, key: String) -> bool {
+        self.data().used_mint_keys.contains(&format!("{}:{}", minter_id, key))
+    }
+
+    pub fn get_max_supply(&self) -> U128 {
+        self.data().max_supply.into()
+    }
+
+    /// amount that can still be minted under max_supply
+    pub fn get_mintable_supply(&self) -> U128 {
+        self.data().max_supply.saturating_sub(self.ft.total_supply).into()
+    }
+
+}
+
+impl Contract {
+    pub(crate) fn assert_mintable(&self, amount: Balance) {
+        let mintable = self.data().max_supply.saturating_sub(self.ft.total_supply);
+        if amount > mintable {
+            env::panic(
+                format!(
+                    "Mint {} exceeds max supply, only {} sa-vBTC left to mint", amount, mintable
+                ).as_bytes());
+        }
+    }
+
+    /// deposit to receiver, registers the account if not yet
+    pub(crate) fn internal_mint(&mut self, receiver_id: &AccountId, amount: Balance) {
+        if !self.ft.accounts.contains_key(receiver_id) {
+            // Not registered, register
+            self.ft.internal_register_account(receiver_id);
+        }
+        self.internal_checkpoint_balance(receiver_id);
+        self.internal_checkpoint_supply();
+        self.ft.internal_deposit(receiver_id, amount);
+    }
+
+    /// keys are stored as "{minter}:{key}", so minters can't use up keys of each other
+    pub(crate) fn internal_use_mint_key(&mut self, minter_id: &AccountId, key: String) {
+        assert!(key.len() <= MAX_MINT_KEY_LEN, "Mint key is too long");
+        if !self.data_mut().used_mint_keys.insert(&format!("{}:{}", minter_id, key)) {
+            env::panic(format!("Mint key {} already used", key).as_bytes());
+        }
+        log!("Use mint key {} of {}", key, minter_id);
+    }
+
+    /// record amount minted by minter, panics if it runs out of quota in current period
+    pub(crate) fn internal_use_quota(&mut self, minter_id: &AccountId, amount: Balance) {
+        let mut minter = self.data().minters.get(minter_id).expect("Only minter can mint vBTC");
+        let now = env::block_timestamp();
+        if now >= minter.period_start + minter.period {
+            minter.period_start = now;
+            minter.minted_in_period = 0;
+        }
+        minter.minted_in_period += amount;
+        if let Some(quota) = minter.quota {
+            if minter.minted_in_period > quota {
+                env::panic(
+                    format!(
+                        "Mint {} exceeds quota of minter, only {} sa-vBTC left in this period",
+                        amount, quota - (minter.minted_in_period - amount)
+                    ).as_bytes());
+            }
+        }
+        self.data_mut().minters.insert(minter_id, &minter);
+    }
 }
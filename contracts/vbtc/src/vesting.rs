@@ -0,0 +1,157 @@
+use crate::*;
+use near_sdk::collections::Vector;
+use near_sdk::json_types::U64;
+use near_sdk::serde::Serialize;
+use near_sdk::{log, Balance, Timestamp};
+
+/// vBTC locked in this contract for receiver, released linearly from start to start + duration,
+/// nothing can be claimed before start + cliff. id is its index in vestings.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct Vesting {
+    pub receiver_id: AccountId,
+    pub total: Balance,
+    pub claimed: Balance,
+    pub start: Timestamp,
+    /// nanoseconds after start
+    pub cliff: u64,
+    /// nanoseconds after start
+    pub duration: u64,
+}
+
+impl Vesting {
+    pub fn vested_at(&self, now: Timestamp) -> Balance {
+        if now < self.start + self.cliff {
+            0
+        } else if now >= self.start + self.duration {
+            self.total
+        } else {
+            self.total * Balance::from(now - self.start) / Balance::from(self.duration)
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct HumanReadableVesting {
+    pub id: U64,
+    pub receiver_id: AccountId,
+    pub total: U128,
+    pub vested: U128,
+    pub claimed: U128,
+    /// locked or vested but not claimed yet
+    pub remaining: U128,
+    pub start: U64,
+    pub cliff: U64,
+    pub duration: U64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// mint total vbtc locked in this contract, and let receiver claim it as it vests.
+    /// Requirements:
+    /// * The predecessor account must be a minter, total is taken from its quota.
+    /// * Caller must attach deposit to cover storage of the schedule, the rest is refunded.
+    /// * Returns id of the vesting.
+    #[payable]
+    pub fn create_vesting(
+        &mut self,
+        receiver_id: ValidAccountId,
+        total: U128,
+        start: U64,
+        cliff: U64,
+        duration: U64,
+    ) -> U64 {
+        self.assert_not_paused(PAUSE_MINT);
+        let initial_storage_usage = env::storage_usage();
+        let total: Balance = total.into();
+        let (cliff, duration): (u64, u64) = (cliff.into(), duration.into());
+        assert!(total > 0, "The total should be a positive number");
+        assert!(duration > 0 && cliff <= duration, "Cliff should be within duration");
+
+        self.internal_use_quota(&env::predecessor_account_id(), total);
+        self.assert_mintable(total);
+        self.internal_mint(&env::current_account_id(), total);
+
+        let id = self.data().vestings.len();
+        self.data_mut().vestings.push(&Vesting {
+            receiver_id: receiver_id.as_ref().clone(),
+            total,
+            claimed: 0,
+            start: start.into(),
+            cliff,
+            duration,
+        });
+        let mut account_vestings = self.data().vestings_per_account.get(receiver_id.as_ref())
+            .unwrap_or_else(|| Vector::new(unique_prefix(b'u', receiver_id.as_ref())));
+        account_vestings.push(&id);
+        self.data_mut().vestings_per_account.insert(receiver_id.as_ref(), &account_vestings);
+        log!("Vesting {} of {} sa-vBTC to {}, start {}, cliff {}, duration {}",
+            id, total, receiver_id, start.0, cliff, duration);
+
+        refund_deposit(initial_storage_usage);
+        id.into()
+    }
+
+    /// transfer all vested but unclaimed vbtc of predecessor's schedules to it,
+    /// returns amount claimed.
+    pub fn claim_vested(&mut self) -> U128 {
+        self.assert_not_paused(PAUSE_TRANSFER);
+        let account_id = env::predecessor_account_id();
+        let ids = self.data().vestings_per_account.get(&account_id).expect("No vesting");
+        let now = env::block_timestamp();
+        let mut amount: Balance = 0;
+        for id in ids.iter() {
+            let mut vesting = self.data().vestings.get(id).unwrap();
+            let claimable = vesting.vested_at(now) - vesting.claimed;
+            if claimable > 0 {
+                vesting.claimed += claimable;
+                amount += claimable;
+                self.data_mut().vestings.replace(id, &vesting);
+            }
+        }
+        assert!(amount > 0, "Nothing vested to claim");
+
+        if !self.ft.accounts.contains_key(&account_id) {
+            self.ft.internal_register_account(&account_id);
+        }
+        let contract_id = env::current_account_id();
+        self.internal_checkpoint_balance(&contract_id);
+        self.internal_checkpoint_balance(&account_id);
+        self.ft.internal_transfer(&contract_id, &account_id, amount, None);
+        log!("Claim {} sa-vBTC vested to {}", amount, account_id);
+        amount.into()
+    }
+
+    //**********************
+    //**** VIEW FUNCTIONS **
+    //**********************
+
+    pub fn get_vesting(&self, id: U64) -> Option<HumanReadableVesting> {
+        let id: u64 = id.into();
+        self.data().vestings.get(id).map(|vesting| vesting_view(id, vesting))
+    }
+
+    /// list vestings whose receiver is the account
+    pub fn list_vestings_by_account(&self, account_id: ValidAccountId) -> Vec<HumanReadableVesting> {
+        match self.data().vestings_per_account.get(account_id.as_ref()) {
+            Some(ids) => ids.iter()
+                .map(|id| vesting_view(id, self.data().vestings.get(id).unwrap()))
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+fn vesting_view(id: u64, vesting: Vesting) -> HumanReadableVesting {
+    HumanReadableVesting {
+        id: id.into(),
+        receiver_id: vesting.receiver_id.clone(),
+        total: vesting.total.into(),
+        vested: vesting.vested_at(env::block_timestamp()).into(),
+        claimed: vesting.claimed.into(),
+        remaining: (vesting.total - vesting.claimed).into(),
+        start: vesting.start.into(),
+        cliff: vesting.cliff.into(),
+        duration: vesting.duration.into(),
+    }
+}